@@ -16,25 +16,59 @@ pub mod vesting {
             treasury_token_account: ctx.accounts.treasury_token_account.key(),
             treasury_bump: ctx.bumps.treasury_token_account,
             vesting_bump: ctx.bumps.vesting_account,
+            total_allocated: 0,
+            total_claimed: 0,
         });
         Ok(())
     }
 
-    pub fn initialize_member_account(ctx: Context<InitializeMemberAccount>, _vesting_id: u64, start_time: i64, end_time: i64, cliff_time: i64, total_amount: u64) -> Result<()> {
+    pub fn fund_treasury(ctx: Context<FundTreasury>, _vesting_id: u64, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let cpi_ix = TransferChecked {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            mint: ctx.accounts.treasury_mint.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_ix);
+
+        let decimals = ctx.accounts.treasury_mint.decimals;
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    pub fn initialize_member_account(ctx: Context<InitializeMemberAccount>, _vesting_id: u64, total_amount: u64, schedule: VestingSchedule, revocable: bool) -> Result<()> {
         require!(total_amount > 0, ErrorCode::InvalidAmount);
-        require!(start_time < cliff_time, ErrorCode::InvalidSchedule);
-        require!(cliff_time <= end_time, ErrorCode::InvalidSchedule);
 
-        ctx.accounts.member_account.set_inner(MemberAccount {
+        let vesting_account = &mut ctx.accounts.vesting_account;
+        let unallocated = vesting_account.unallocated(ctx.accounts.treasury_token_account.amount);
+        require!(total_amount <= unallocated, ErrorCode::InsufficientTreasuryFunds);
+        vesting_account.total_allocated += total_amount;
+
+        let VestingSchedule { start_time, end_time, cliff_time, schedule_kind, period_seconds, checkpoints } = schedule;
+        let member_account = MemberAccount {
             beneficiary: ctx.accounts.beneficiary.key(),
             start_time,
             end_time,
             cliff_time,
+            schedule_kind,
+            period_seconds,
+            checkpoints,
             total_amount,
             total_withdrawn: 0,
             vesting_account: ctx.accounts.vesting_account.key(),
             member_bump: ctx.bumps.member_account,
-        });
+            revocable,
+            revoked: false,
+            revoked_at: 0,
+        };
+        member_account.validate_schedule()?;
+
+        ctx.accounts.member_account.set_inner(member_account);
         Ok(())
     }
 
@@ -46,14 +80,7 @@ pub mod vesting {
             return Err(ErrorCode::NotCliffTime.into());
         }
 
-        let time_since_start = now.saturating_sub(member_account.start_time) as u64;
-        let vesting_duration = member_account.end_time.saturating_sub(member_account.start_time) as u64;
-
-        let vested_amount = if now > member_account.end_time {
-            member_account.total_amount
-        } else {
-            member_account.total_amount.saturating_mul(time_since_start).saturating_div(vesting_duration)
-        };
+        let vested_amount = member_account.vested_amount(now);
 
         let claimable_amount = vested_amount.saturating_sub(member_account.total_withdrawn);
 
@@ -81,6 +108,49 @@ pub mod vesting {
         transfer_checked(cpi_ctx, claimable_amount, decimals)?;
 
         member_account.total_withdrawn += claimable_amount;
+        ctx.accounts.vesting_account.total_claimed += claimable_amount;
+        Ok(())
+    }
+
+    /// Stops vesting at the current time. Everything vested so far stays claimable by the
+    /// beneficiary, and the unvested remainder is returned from the treasury to the owner.
+    pub fn revoke_member(ctx: Context<RevokeMember>, _vesting_id: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let member_account = &mut ctx.accounts.member_account;
+
+        require!(member_account.revocable, ErrorCode::NotRevocable);
+        require!(!member_account.revoked, ErrorCode::AlreadyRevoked);
+
+        let unvested_amount = member_account.total_amount.saturating_sub(member_account.vested_amount(now));
+
+        member_account.revoked = true;
+        member_account.revoked_at = now;
+        ctx.accounts.vesting_account.total_allocated -= unvested_amount;
+
+        if unvested_amount == 0 {
+            return Ok(());
+        }
+
+        let binding = ctx.accounts.vesting_account.vesting_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vesting_treasury", binding.as_ref(),
+             &[ctx.accounts.vesting_account.treasury_bump],
+        ]];
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let cpi_ix = TransferChecked {
+            from: ctx.accounts.treasury_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            mint: ctx.accounts.treasury_mint.to_account_info(),
+            authority: ctx.accounts.treasury_token_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_ix).with_signer(signer_seeds);
+
+        let decimals = ctx.accounts.treasury_mint.decimals;
+
+        transfer_checked(cpi_ctx, unvested_amount, decimals)?;
         Ok(())
     }
 }
@@ -114,6 +184,35 @@ pub struct InitializeVestingAccount<'info> {
 }
 
 
+#[derive(Accounts)]
+#[instruction(vesting_id: u64)]
+pub struct FundTreasury<'info> {
+    pub funder: Signer<'info>,
+    #[account(
+        seeds = [b"vesting", vesting_account.vesting_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = treasury_mint,
+        has_one = treasury_token_account,
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+    #[account(
+        mut,
+        token::mint = treasury_mint,
+        token::authority = funder,
+        token::token_program = token_program,
+    )]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vesting_treasury", vesting_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub treasury_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+
 #[derive(Accounts)]
 #[instruction(vesting_id: u64)]
 pub struct InitializeMemberAccount<'info> {
@@ -121,11 +220,14 @@ pub struct InitializeMemberAccount<'info> {
     pub owner: Signer<'info>,
     pub beneficiary: SystemAccount<'info>,
     #[account(
+        mut,
         seeds = [b"vesting", vesting_account.vesting_id.to_le_bytes().as_ref()],
         bump,
         has_one = owner,
+        has_one = treasury_token_account,
     )]
     pub vesting_account: Account<'info, VestingAccount>,
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init,
         payer = owner,
@@ -144,6 +246,7 @@ pub struct ClaimTokens<'info> {
     #[account(mut)]
     pub beneficiary: Signer<'info>,
     #[account(
+        mut,
         seeds = [b"vesting", vesting_account.vesting_id.to_le_bytes().as_ref()],
         bump,
         has_one = treasury_mint,
@@ -178,6 +281,43 @@ pub struct ClaimTokens<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[derive(Accounts)]
+#[instruction(vesting_id: u64)]
+pub struct RevokeMember<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vesting", vesting_account.vesting_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = treasury_mint,
+        has_one = treasury_token_account,
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+    #[account(
+        mut,
+        seeds = [b"member", member_account.beneficiary.as_ref(), vesting_account.key().as_ref()],
+        bump,
+        has_one = vesting_account,
+    )]
+    pub member_account: Account<'info, MemberAccount>,
+    #[account(
+        mut,
+        token::mint = treasury_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vesting_treasury", vesting_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub treasury_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct VestingAccount {
@@ -187,6 +327,19 @@ pub struct VestingAccount {
     treasury_token_account: Pubkey,
     treasury_bump: u8,
     vesting_bump: u8,
+    /// Sum of `total_amount` over all grants, less anything clawed back by revocation.
+    pub total_allocated: u64,
+    /// Sum of `total_withdrawn` over all grants.
+    pub total_claimed: u64,
+}
+
+impl VestingAccount {
+    /// Tokens in the treasury not yet promised to any grant.
+    pub fn unallocated(&self, treasury_balance: u64) -> u64 {
+        treasury_balance
+            .saturating_add(self.total_claimed)
+            .saturating_sub(self.total_allocated)
+    }
 }
 
 #[account]
@@ -196,10 +349,134 @@ pub struct MemberAccount {
     pub start_time: i64,
     pub end_time: i64,
     pub cliff_time: i64,
+    pub schedule_kind: ScheduleKind,
+    /// Length of each unlock step in seconds; 0 vests continuously. Ignored for `Custom`.
+    pub period_seconds: i64,
+    /// Unlock checkpoints for `Custom` schedules; empty otherwise.
+    #[max_len(MAX_CHECKPOINTS)]
+    pub checkpoints: Vec<Checkpoint>,
     pub total_amount: u64,
     pub total_withdrawn: u64,
     pub vesting_account: Pubkey,
     pub member_bump: u8,
+    /// Whether the vesting owner may revoke this grant. Set at creation.
+    pub revocable: bool,
+    pub revoked: bool,
+    /// Vesting stops at this time once `revoked` is set.
+    pub revoked_at: i64,
+}
+
+pub const MAX_CHECKPOINTS: usize = 16;
+
+/// How tokens vest between `start_time`, `cliff_time` and `end_time`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ScheduleKind {
+    /// Vests linearly from `start_time` to `end_time`. Requires `cliff_time == start_time`.
+    Linear,
+    /// Vests linearly from `start_time`, but nothing is claimable before `cliff_time`;
+    /// at the cliff everything accrued since `start_time` unlocks at once.
+    LinearWithCliff,
+    /// Nothing vests before `cliff_time`, then vests linearly from `cliff_time` to `end_time`.
+    CliffThenLinear,
+    /// Vests according to `checkpoints`. `cliff_time` and `end_time` must match the first and last checkpoint.
+    Custom,
+}
+
+/// Schedule fields of a new grant, validated together by `MemberAccount::validate_schedule`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub cliff_time: i64,
+    pub schedule_kind: ScheduleKind,
+    pub period_seconds: i64,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// Once `timestamp` is reached, `cumulative_amount` tokens in total have vested.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Checkpoint {
+    pub timestamp: i64,
+    pub cumulative_amount: u64,
+}
+
+impl MemberAccount {
+    pub fn validate_schedule(&self) -> Result<()> {
+        let (start_time, cliff_time, end_time) = (self.start_time, self.cliff_time, self.end_time);
+
+        match self.schedule_kind {
+            ScheduleKind::Linear => {
+                require!(start_time == cliff_time, ErrorCode::InvalidSchedule);
+                require!(start_time < end_time, ErrorCode::InvalidSchedule);
+            }
+            ScheduleKind::LinearWithCliff => {
+                require!(start_time < cliff_time, ErrorCode::InvalidSchedule);
+                require!(cliff_time <= end_time, ErrorCode::InvalidSchedule);
+            }
+            ScheduleKind::CliffThenLinear => {
+                require!(start_time <= cliff_time, ErrorCode::InvalidSchedule);
+                require!(cliff_time < end_time, ErrorCode::InvalidSchedule);
+            }
+            ScheduleKind::Custom => {
+                require!(self.period_seconds == 0, ErrorCode::InvalidSchedule);
+                return self.validate_checkpoints();
+            }
+        }
+
+        require!(self.period_seconds >= 0, ErrorCode::InvalidSchedule);
+        require!(self.checkpoints.is_empty(), ErrorCode::InvalidCheckpoints);
+        Ok(())
+    }
+
+    fn validate_checkpoints(&self) -> Result<()> {
+        let (first, last) = match (self.checkpoints.first(), self.checkpoints.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(ErrorCode::InvalidCheckpoints.into()),
+        };
+        require!(self.checkpoints.len() <= MAX_CHECKPOINTS, ErrorCode::InvalidCheckpoints);
+        require!(
+            self.checkpoints.windows(2).all(|pair| {
+                pair[0].timestamp < pair[1].timestamp && pair[0].cumulative_amount <= pair[1].cumulative_amount
+            }),
+            ErrorCode::InvalidCheckpoints
+        );
+        require!(last.cumulative_amount == self.total_amount, ErrorCode::InvalidCheckpoints);
+
+        require!(self.start_time <= first.timestamp, ErrorCode::InvalidSchedule);
+        require!(self.cliff_time == first.timestamp, ErrorCode::InvalidSchedule);
+        require!(self.end_time == last.timestamp, ErrorCode::InvalidSchedule);
+        Ok(())
+    }
+
+    /// Total amount vested at `now`, including anything already withdrawn.
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        let now = if self.revoked { now.min(self.revoked_at) } else { now };
+        if now < self.cliff_time {
+            return 0;
+        }
+        if now >= self.end_time {
+            return self.total_amount;
+        }
+
+        let vesting_start = match self.schedule_kind {
+            ScheduleKind::Linear | ScheduleKind::LinearWithCliff => self.start_time,
+            ScheduleKind::CliffThenLinear => self.cliff_time,
+            ScheduleKind::Custom => {
+                return self.checkpoints
+                    .iter()
+                    .take_while(|checkpoint| checkpoint.timestamp <= now)
+                    .last()
+                    .map_or(0, |checkpoint| checkpoint.cumulative_amount);
+            }
+        };
+        let mut elapsed = now.saturating_sub(vesting_start);
+        if self.period_seconds > 0 {
+            elapsed -= elapsed % self.period_seconds;
+        }
+        let duration = self.end_time.saturating_sub(vesting_start) as u128;
+
+        (self.total_amount as u128 * elapsed as u128 / duration) as u64
+    }
 }
 
 #[error_code]
@@ -210,6 +487,204 @@ pub enum ErrorCode {
     NothingToClaim,
     #[msg("Invalid amount")]
     InvalidAmount,
-    #[msg("Invalid schedule: must satisfy start_time < cliff_time <= end_time")]
+    #[msg("Invalid schedule: start_time, cliff_time and end_time do not fit the schedule kind")]
     InvalidSchedule,
+    #[msg("Invalid checkpoints: timestamps must increase and the last cumulative amount must equal total_amount")]
+    InvalidCheckpoints,
+    #[msg("Grant is not revocable")]
+    NotRevocable,
+    #[msg("Grant has already been revoked")]
+    AlreadyRevoked,
+    #[msg("Grant exceeds the unallocated treasury balance")]
+    InsufficientTreasuryFunds,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(schedule_kind: ScheduleKind, start_time: i64, cliff_time: i64, end_time: i64) -> MemberAccount {
+        MemberAccount {
+            beneficiary: Pubkey::default(),
+            start_time,
+            end_time,
+            cliff_time,
+            schedule_kind,
+            period_seconds: 0,
+            checkpoints: Vec::new(),
+            total_amount: 1_000,
+            total_withdrawn: 0,
+            vesting_account: Pubkey::default(),
+            member_bump: 0,
+            revocable: false,
+            revoked: false,
+            revoked_at: 0,
+        }
+    }
+
+    #[test]
+    fn linear_vests_from_start() {
+        let member = member(ScheduleKind::Linear, 100, 100, 200);
+        assert_eq!(member.vested_amount(99), 0);
+        assert_eq!(member.vested_amount(100), 0);
+        assert_eq!(member.vested_amount(125), 250);
+        assert_eq!(member.vested_amount(150), 500);
+        assert_eq!(member.vested_amount(200), 1_000);
+        assert_eq!(member.vested_amount(300), 1_000);
+    }
+
+    #[test]
+    fn linear_with_cliff_unlocks_accrued_amount_at_cliff() {
+        let member = member(ScheduleKind::LinearWithCliff, 0, 300, 500);
+        assert_eq!(member.vested_amount(299), 0);
+        assert_eq!(member.vested_amount(300), 600);
+        assert_eq!(member.vested_amount(400), 800);
+        assert_eq!(member.vested_amount(500), 1_000);
+    }
+
+    #[test]
+    fn cliff_then_linear_vests_from_cliff() {
+        let member = member(ScheduleKind::CliffThenLinear, 0, 300, 500);
+        assert_eq!(member.vested_amount(299), 0);
+        assert_eq!(member.vested_amount(300), 0);
+        assert_eq!(member.vested_amount(400), 500);
+        assert_eq!(member.vested_amount(500), 1_000);
+    }
+
+    #[test]
+    fn vested_amount_does_not_overflow() {
+        let mut member = member(ScheduleKind::Linear, 0, 0, 1_000);
+        member.total_amount = u64::MAX;
+        assert_eq!(member.vested_amount(500), u64::MAX / 2);
+    }
+
+    fn custom(checkpoints: &[(i64, u64)]) -> MemberAccount {
+        let checkpoints: Vec<Checkpoint> = checkpoints
+            .iter()
+            .map(|&(timestamp, cumulative_amount)| Checkpoint { timestamp, cumulative_amount })
+            .collect();
+        let first = checkpoints.first().map_or(0, |checkpoint| checkpoint.timestamp);
+        let last = checkpoints.last().map_or(0, |checkpoint| checkpoint.timestamp);
+        let mut member = member(ScheduleKind::Custom, 0, first, last);
+        member.checkpoints = checkpoints;
+        member
+    }
+
+    #[test]
+    fn periodic_linear_vests_in_steps() {
+        let mut member = member(ScheduleKind::Linear, 0, 0, 400);
+        member.period_seconds = 100;
+        assert_eq!(member.vested_amount(99), 0);
+        assert_eq!(member.vested_amount(100), 250);
+        assert_eq!(member.vested_amount(199), 250);
+        assert_eq!(member.vested_amount(350), 750);
+        assert_eq!(member.vested_amount(400), 1_000);
+    }
+
+    #[test]
+    fn periodic_schedule_unlocks_remainder_at_end() {
+        // 300 is not a multiple of the period, so the last step is shorter
+        let mut member = member(ScheduleKind::CliffThenLinear, 0, 0, 300);
+        member.period_seconds = 200;
+        assert_eq!(member.vested_amount(199), 0);
+        assert_eq!(member.vested_amount(299), 666);
+        assert_eq!(member.vested_amount(300), 1_000);
+    }
+
+    #[test]
+    fn custom_vests_at_checkpoints() {
+        let member = custom(&[(100, 100), (200, 100), (300, 600), (400, 1_000)]);
+        assert!(member.validate_schedule().is_ok());
+        assert_eq!(member.vested_amount(99), 0);
+        assert_eq!(member.vested_amount(100), 100);
+        assert_eq!(member.vested_amount(250), 100);
+        assert_eq!(member.vested_amount(300), 600);
+        assert_eq!(member.vested_amount(399), 600);
+        assert_eq!(member.vested_amount(400), 1_000);
+    }
+
+    #[test]
+    fn validates_custom_checkpoints() {
+        assert!(custom(&[]).validate_schedule().is_err());
+        assert!(custom(&[(100, 1_000)]).validate_schedule().is_ok());
+        assert!(custom(&[(100, 500), (100, 1_000)]).validate_schedule().is_err());
+        assert!(custom(&[(100, 600), (200, 500), (300, 1_000)]).validate_schedule().is_err());
+        assert!(custom(&[(100, 500), (200, 900)]).validate_schedule().is_err());
+
+        let too_many: Vec<(i64, u64)> = (0..=MAX_CHECKPOINTS as i64).map(|i| (i, 1_000)).collect();
+        assert!(custom(&too_many).validate_schedule().is_err());
+
+        let mut periodic = custom(&[(100, 500), (200, 1_000)]);
+        periodic.period_seconds = 10;
+        assert!(periodic.validate_schedule().is_err());
+
+        let mut linear = member(ScheduleKind::Linear, 0, 0, 100);
+        linear.checkpoints = vec![Checkpoint { timestamp: 100, cumulative_amount: 1_000 }];
+        assert!(linear.validate_schedule().is_err());
+    }
+
+    #[test]
+    fn revocation_stops_vesting() {
+        let mut linear = member(ScheduleKind::Linear, 0, 0, 1_000);
+        linear.revoked = true;
+        linear.revoked_at = 400;
+        assert_eq!(linear.vested_amount(300), 300);
+        assert_eq!(linear.vested_amount(400), 400);
+        assert_eq!(linear.vested_amount(2_000), 400);
+
+        let mut before_cliff = member(ScheduleKind::LinearWithCliff, 0, 500, 1_000);
+        before_cliff.revoked = true;
+        before_cliff.revoked_at = 400;
+        assert_eq!(before_cliff.vested_amount(2_000), 0);
+    }
+
+    #[test]
+    fn unallocated_accounts_for_claims() {
+        let mut vesting = VestingAccount {
+            vesting_id: 1,
+            owner: Pubkey::default(),
+            treasury_mint: Pubkey::default(),
+            treasury_token_account: Pubkey::default(),
+            treasury_bump: 0,
+            vesting_bump: 0,
+            total_allocated: 0,
+            total_claimed: 0,
+        };
+        assert_eq!(vesting.unallocated(1_000), 1_000);
+
+        vesting.total_allocated = 600;
+        assert_eq!(vesting.unallocated(1_000), 400);
+
+        // 200 claimed: the balance drops, but so does the outstanding obligation
+        vesting.total_claimed = 200;
+        assert_eq!(vesting.unallocated(800), 400);
+
+        // Over-committed treasuries have nothing left to grant
+        assert_eq!(vesting.unallocated(100), 0);
+    }
+
+    #[test]
+    fn validates_schedule_per_kind() {
+        let validate = |schedule_kind, start_time, cliff_time, end_time| {
+            member(schedule_kind, start_time, cliff_time, end_time).validate_schedule()
+        };
+
+        assert!(validate(ScheduleKind::Linear, 0, 0, 100).is_ok());
+        assert!(validate(ScheduleKind::Linear, 0, 10, 100).is_err());
+        assert!(validate(ScheduleKind::Linear, 100, 100, 100).is_err());
+
+        assert!(validate(ScheduleKind::LinearWithCliff, 0, 10, 100).is_ok());
+        assert!(validate(ScheduleKind::LinearWithCliff, 0, 100, 100).is_ok());
+        assert!(validate(ScheduleKind::LinearWithCliff, 0, 0, 100).is_err());
+        assert!(validate(ScheduleKind::LinearWithCliff, 0, 110, 100).is_err());
+
+        assert!(validate(ScheduleKind::CliffThenLinear, 0, 0, 100).is_ok());
+        assert!(validate(ScheduleKind::CliffThenLinear, 0, 10, 100).is_ok());
+        assert!(validate(ScheduleKind::CliffThenLinear, 0, 100, 100).is_err());
+        assert!(validate(ScheduleKind::CliffThenLinear, 10, 0, 100).is_err());
+
+        let mut negative_period = member(ScheduleKind::Linear, 0, 0, 100);
+        negative_period.period_seconds = -1;
+        assert!(negative_period.validate_schedule().is_err());
+    }
 }
@@ -4,9 +4,9 @@ import { BankrunProvider } from "anchor-bankrun";
 import { BanksClient, startAnchor, ProgramTestContext } from "solana-bankrun";
 import { Program } from "@coral-xyz/anchor";
 import { Vesting } from "../target/types/vesting";
-import { createMint, mintTo } from "spl-token-bankrun";
+import { createAssociatedTokenAccount, createMint, mintTo } from "spl-token-bankrun";
 import { TOKEN_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { AccountLayout, getAssociatedTokenAddressSync } from "@solana/spl-token";
 
 describe("vesting", () => {
 
@@ -27,6 +27,12 @@ describe("vesting", () => {
   let vestingAccountPda: anchor.web3.PublicKey;
   let memberAccountPda: anchor.web3.PublicKey;
   let treasuryTokenAccountPda: anchor.web3.PublicKey;
+  let ownerTokenAccount: anchor.web3.PublicKey;
+
+  const tokenBalance = async (address: anchor.web3.PublicKey) => {
+    const account = await banksClient.getAccount(address);
+    return AccountLayout.decode(account.data).amount;
+  };
 
   before(async () => {
     beneficiary = new anchor.web3.Keypair();
@@ -77,31 +83,52 @@ describe("vesting", () => {
     }).rpc();
 
   });
-  it("should initialize member account", async () => {
-
-    await program.methods.initializeMemberAccount(
-      vestingId,
-      startTime,
-      endTime,
-      cliffTime,
-      totalAmount,
-    ).accounts({
-      owner: member.publicKey,
-      beneficiary: beneficiary.publicKey,
-      vestingAccount: vestingAccountPda,
-    }).rpc();
-  });
   it("should fund to treasury token account", async () => {
 
     const amount = new anchor.BN(100_000_000);
+    ownerTokenAccount = await createAssociatedTokenAccount(
+      banksClient,
+      member,
+      mint,
+      member.publicKey,
+    );
     await mintTo(
       banksClient,
       member,
       mint,
-      treasuryTokenAccountPda,
+      ownerTokenAccount,
       member,
       amount,
     );
+
+    await program.methods.fundTreasury(vestingId, amount).accounts({
+      funder: member.publicKey,
+      vestingAccount: vestingAccountPda,
+      funderTokenAccount: ownerTokenAccount,
+      treasuryTokenAccount: treasuryTokenAccountPda,
+      treasuryMint: mint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+  });
+  it("should initialize member account", async () => {
+
+    await program.methods.initializeMemberAccount(
+      vestingId,
+      totalAmount,
+      {
+        startTime,
+        endTime,
+        cliffTime,
+        scheduleKind: { linearWithCliff: {} },
+        periodSeconds: new anchor.BN(0),
+        checkpoints: [],
+      },
+      false,
+    ).accounts({
+      owner: member.publicKey,
+      beneficiary: beneficiary.publicKey,
+      vestingAccount: vestingAccountPda,
+    }).rpc();
   });
   it("should claim tokens", async () => {
 
@@ -134,4 +161,117 @@ describe("vesting", () => {
       memberTokenAccount: beneficiaryAta,
     }).signers([beneficiary]).rpc();
   });
+  it("should revoke a revocable member", async () => {
+    const revokedBeneficiary = new anchor.web3.Keypair();
+    context.setAccount(revokedBeneficiary.publicKey, {
+      executable: false,
+      owner: SYSTEM_PROGRAM_ID,
+      lamports: 1_000_000_000,
+      data: Buffer.alloc(0),
+    });
+    const [revokedMemberPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("member"), revokedBeneficiary.publicKey.toBuffer(), vestingAccountPda.toBuffer()],
+      programId
+    );
+
+    await program.methods.initializeMemberAccount(
+      vestingId,
+      totalAmount,
+      {
+        startTime: new anchor.BN(0),
+        endTime: new anchor.BN(1_000),
+        cliffTime: new anchor.BN(0),
+        scheduleKind: { linear: {} },
+        periodSeconds: new anchor.BN(0),
+        checkpoints: [],
+      },
+      true,
+    ).accounts({
+      owner: member.publicKey,
+      beneficiary: revokedBeneficiary.publicKey,
+      vestingAccount: vestingAccountPda,
+    }).rpc();
+
+    // The clock is at 400, so 40% has vested and the remaining 60% goes back to the owner
+    const ownerBalanceBefore = await tokenBalance(ownerTokenAccount);
+    await program.methods.revokeMember(vestingId).accounts({
+      owner: member.publicKey,
+      vestingAccount: vestingAccountPda,
+      memberAccount: revokedMemberPda,
+      ownerTokenAccount,
+      treasuryTokenAccount: treasuryTokenAccountPda,
+      treasuryMint: mint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+
+    const revokedMember = await program.account.memberAccount.fetch(revokedMemberPda);
+    if (!revokedMember.revoked || revokedMember.revokedAt.toNumber() !== 400) {
+      throw new Error("member was not revoked");
+    }
+    const returned = (await tokenBalance(ownerTokenAccount)) - ownerBalanceBefore;
+    if (returned !== BigInt(600_000)) {
+      throw new Error(`owner received ${returned} instead of the unvested 600000`);
+    }
+
+    // Vesting stopped at the revoke, so even after the end time only the vested 40% is claimable
+    const currentClock = await banksClient.getClock();
+    context.setClock(
+      new (await import("solana-bankrun")).Clock(
+        currentClock.slot,
+        currentClock.epochStartTimestamp,
+        currentClock.epoch,
+        currentClock.leaderScheduleEpoch,
+        BigInt(2_000),
+      )
+    );
+
+    const revokedBeneficiaryAta = getAssociatedTokenAddressSync(
+      mint,
+      revokedBeneficiary.publicKey,
+      false,
+      TOKEN_PROGRAM_ID,
+    );
+    await program.methods.claimTokens(vestingId).accounts({
+      beneficiary: revokedBeneficiary.publicKey,
+      vestingAccount: vestingAccountPda,
+      memberAccount: revokedMemberPda,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      treasuryTokenAccount: treasuryTokenAccountPda,
+      treasuryMint: mint,
+      memberTokenAccount: revokedBeneficiaryAta,
+    }).signers([revokedBeneficiary]).rpc();
+
+    const claimed = await tokenBalance(revokedBeneficiaryAta);
+    if (claimed !== BigInt(400_000)) {
+      throw new Error(`beneficiary claimed ${claimed} instead of the vested 400000`);
+    }
+  });
+  it("should reject grants beyond the treasury balance", async () => {
+    const overBeneficiary = new anchor.web3.Keypair();
+
+    try {
+      await program.methods.initializeMemberAccount(
+        vestingId,
+        new anchor.BN(100_000_000),
+        {
+          startTime: new anchor.BN(0),
+          endTime: new anchor.BN(1_000),
+          cliffTime: new anchor.BN(0),
+          scheduleKind: { linear: {} },
+          periodSeconds: new anchor.BN(0),
+          checkpoints: [],
+        },
+        false,
+      ).accounts({
+        owner: member.publicKey,
+        beneficiary: overBeneficiary.publicKey,
+        vestingAccount: vestingAccountPda,
+      }).rpc();
+      throw new Error("over-committed grant was accepted");
+    } catch (err) {
+      if (!err.toString().includes("InsufficientTreasuryFunds")) {
+        throw err;
+      }
+    }
+  });
 });
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+#[constant]
+pub const NAME: &str = "Token Lottery Ticket #";
+/// base_uriの最大長
+/// Metaplexのuriの上限（200文字）から、"/winner/{ticket_id}.json" の分を引いた長さ
+#[constant]
+pub const MAX_BASE_URI_LENGTH: u8 = 160;
+/// Metaplexのsymbolの上限
+#[constant]
+pub const MAX_SYMBOL_LENGTH: u8 = 10;
+#[constant]
+pub const MAX_PRIZE_TIERS: u8 = 5;
+#[constant]
+pub const BASIS_POINTS: u16 = 10_000;
+/// keeper_tipの上限（チケット1枚の価格に対するbasis points）
+/// チケットが少ない場合でも、キーパーへの報酬が賞金プールの大半を占めないようにする
+#[constant]
+pub const MAX_KEEPER_TIP_BPS: u16 = 1_000;
+/// lottery_endからこのスロット数が経過しても当選者が決まらない場合、誰でも抽選を中止できる
+/// （約1日: 1スロット = 約400ms）
+#[cfg(not(feature = "mock-randomness"))]
+#[constant]
+pub const CANCEL_TIMEOUT_SLOTS: u64 = 216_000;
+/// CANCEL_TIMEOUT_SLOTSのタイムスタンプ版（1日）
+#[cfg(not(feature = "mock-randomness"))]
+#[constant]
+pub const CANCEL_TIMEOUT_SECONDS: u64 = 86_400;
+// mock-randomness（ローカルテスト用ビルド）では、乱数が解決されなかった場合の中止と返金を
+// テストできるよう短くする
+#[cfg(feature = "mock-randomness")]
+#[constant]
+pub const CANCEL_TIMEOUT_SLOTS: u64 = 40;
+#[cfg(feature = "mock-randomness")]
+#[constant]
+pub const CANCEL_TIMEOUT_SECONDS: u64 = 20;
+/// 当選者が決まってから賞金を受け取れる期間（約7日）
+/// 期限を過ぎると、start_next_roundで未受け取りの賞金が次のラウンドに繰り越される
+#[constant]
+pub const CLAIM_WINDOW_SLOTS: u64 = 1_512_000;
+/// CommitRevealモードで、lottery_end後にシードを公開できる期間（約10分）
+#[cfg(not(feature = "mock-randomness"))]
+#[constant]
+pub const SEED_REVEAL_WINDOW_SLOTS: u64 = 1_500;
+/// SEED_REVEAL_WINDOW_SLOTSのタイムスタンプ版（10分）
+#[cfg(not(feature = "mock-randomness"))]
+#[constant]
+pub const SEED_REVEAL_WINDOW_SECONDS: u64 = 600;
+// mock-randomness（ローカルテスト用ビルド）では、公開期間の終了を待てるよう短くする
+#[cfg(feature = "mock-randomness")]
+#[constant]
+pub const SEED_REVEAL_WINDOW_SLOTS: u64 = 20;
+#[cfg(feature = "mock-randomness")]
+#[constant]
+pub const SEED_REVEAL_WINDOW_SECONDS: u64 = 10;
+/// Switchboard On-Demandのプログラム（mainnet, devnet）
+pub const SWITCHBOARD_PROGRAM_IDS: [Pubkey; 2] = [
+    pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv"),
+    pubkey!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2"),
+];
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Incorrect randomness account")]
+    IncorrectRandomnessAccount,
+    #[msg("Lottery not completed")]
+    LotteryNotCompleted,
+    #[msg("Lottery is not open")]
+    LotteryNotOpen,
+    #[msg("Not authorized")]
+    NotAuthorized,
+    #[msg("Randomness already revealed")]
+    RandomnessAlreadyRevealed,
+    #[msg("Randomness not resolved")]
+    RandomnessNotResolved,
+    #[msg("Winner not chosen")]
+    WinnerNotChosen,
+    #[msg("Winner already chosen")]
+    WinnerChosen,
+    #[msg("Ticket is not verified")]
+    NotVerifiedTicket,
+    #[msg("Incorrect ticket")]
+    IncorrectTicket,
+    #[msg("No tickets sold")]
+    NoTicketsSold,
+    #[msg("Ticket has no collection")]
+    NoCollection,
+    #[msg("Prize tiers must be 1 to 5 non-zero shares summing to 10000 basis points")]
+    InvalidPrizeTiers,
+    #[msg("Invalid prize tier")]
+    InvalidTier,
+    #[msg("Prize already claimed")]
+    PrizeAlreadyClaimed,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Payment mint does not match the lottery")]
+    InvalidPaymentMint,
+    #[msg("Token payment accounts are required for this lottery")]
+    MissingPaymentAccounts,
+    #[msg("Fee must be less than 10000 basis points")]
+    InvalidFee,
+    #[msg("Fee recipient does not match the lottery")]
+    InvalidFeeRecipient,
+    #[msg("No fees to withdraw")]
+    NoFeesToWithdraw,
+    #[msg("Lottery has been cancelled")]
+    LotteryCancelled,
+    #[msg("Lottery is not cancelled")]
+    LotteryNotCancelled,
+    #[msg("Cancellation timeout has not been reached")]
+    CancelTimeoutNotReached,
+    #[msg("Max tickets must be greater than zero")]
+    InvalidMaxTickets,
+    #[msg("Quantity must be greater than zero")]
+    InvalidQuantity,
+    #[msg("Not enough tickets left")]
+    MaxTicketsExceeded,
+    #[msg("Wallet ticket limit exceeded")]
+    WalletTicketLimitExceeded,
+    #[msg("Instruction does not match the lottery's ticket mode")]
+    InvalidTicketMode,
+    #[msg("Randomness account is not owned by Switchboard")]
+    InvalidRandomnessOwner,
+    #[msg("Randomness account data is invalid")]
+    InvalidRandomnessAccount,
+    #[msg("Randomness has already been committed")]
+    RandomnessAlreadyCommitted,
+    #[msg("Instruction does not match the lottery's randomness mode")]
+    InvalidRandomnessMode,
+    #[msg("Seed reveal window is not open")]
+    SeedRevealNotOpen,
+    #[msg("Seed has already been revealed")]
+    SeedAlreadyRevealed,
+    #[msg("Seed does not match the committed hash")]
+    SeedHashMismatch,
+    #[msg("Current round has not finished")]
+    RoundNotFinished,
+    #[msg("Ticket belongs to a previous round")]
+    TicketFromPreviousRound,
+    #[msg("Lottery start must be before end")]
+    InvalidLotteryWindow,
+    #[msg("Ticket price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Config cannot be changed after tickets are sold")]
+    TicketsAlreadySold,
+    #[msg("Base URI or symbol is too long")]
+    InvalidMetadata,
+    #[msg("Instruction not available in this prize mode")]
+    InvalidPrizeMode,
+    #[msg("Reward rate must not exceed 100%")]
+    InvalidRewardRate,
+    #[msg("Yield has already been harvested for this round")]
+    YieldAlreadyHarvested,
+    #[msg("Yield must be harvested before drawing winners")]
+    YieldNotHarvested,
+    #[msg("Deposit cannot be withdrawn until the round is drawn")]
+    DepositLocked,
+    #[msg("Pot does not match the lottery")]
+    InvalidPot,
+    #[msg("Payment, fee recipient, modes and metadata cannot be changed")]
+    ImmutableConfig,
+    #[msg("No pot left to withdraw after the refunds")]
+    NoPotToWithdraw,
+    #[msg("Prize must be claimed before withdrawing a winning ticket")]
+    PrizeNotClaimed,
+    #[msg("Keeper tip exceeds the allowed share of the ticket price")]
+    InvalidKeeperTip,
+    #[msg("Payment token account is not owned by the recipient")]
+    InvalidPaymentAccount,
+}
@@ -0,0 +1,55 @@
+//! インデクサーや監査ページ向けのイベント
+//! ログ文字列を解析しなくても、購入から抽選・受け取りまでを検証できる
+
+use anchor_lang::prelude::*;
+
+/// チケットの購入（まとめ買いの場合は1回の購入につき1イベント）
+#[event]
+pub struct TicketPurchased {
+    pub lottery_id: u64,
+    pub round: u64,
+    pub buyer: Pubkey,
+    /// 購入したチケットIDの範囲の先頭（ticket_id..ticket_id + quantity）
+    pub ticket_id: u64,
+    pub quantity: u64,
+    /// チケットNFTのミント（Compactモードではデフォルト値）
+    pub mint: Pubkey,
+    /// 支払った代金の合計（手数料を含む）
+    pub price: u64,
+}
+
+/// 抽選に使うランダムネスアカウントのコミット
+#[event]
+pub struct WinnerCommitted {
+    pub lottery_id: u64,
+    pub round: u64,
+    pub randomness_account: Pubkey,
+    pub seed_slot: u64,
+}
+
+/// 当選者の決定
+/// random_value・total_tickets・round_first_ticket_idから、誰でもwinning_ticket_idsを再計算できる
+#[event]
+pub struct WinnerRevealed {
+    pub lottery_id: u64,
+    pub round: u64,
+    pub random_value: [u8; 32],
+    /// このラウンドで販売されたチケット数
+    pub total_tickets: u64,
+    /// このラウンドの最初のチケットID（select_winnersの結果に足したものが当選チケットID）
+    pub round_first_ticket_id: u64,
+    /// 賞ごとの当選チケットID（1等から順）
+    pub winning_ticket_ids: Vec<u64>,
+    pub prize_pool: u64,
+}
+
+/// 賞金の受け取り
+#[event]
+pub struct PrizeClaimed {
+    pub lottery_id: u64,
+    pub round: u64,
+    pub winner: Pubkey,
+    pub tier: u8,
+    pub ticket_id: u64,
+    pub amount: u64,
+}
@@ -0,0 +1,431 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
+};
+use anchor_spl::metadata::{
+    Metadata,
+    CreateMetadataAccountsV3,
+    CreateMasterEditionV3,
+    SetAndVerifySizedCollectionItem,
+    create_master_edition_v3,
+    create_metadata_accounts_v3,
+    set_and_verify_sized_collection_item,
+    mpl_token_metadata::types::DataV2,
+};
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::events::TicketPurchased;
+use crate::instructions::payment::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct BuyTicket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [token_lottery.lottery_id.to_le_bytes().as_ref(), token_lottery.total_tickets.to_le_bytes().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = collection_mint,
+        mint::freeze_authority = collection_mint,
+        mint::token_program = token_program
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Ticket::INIT_SPACE,
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), token_lottery.total_tickets.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + BuyerRecord::INIT_SPACE,
+        seeds = [b"buyer".as_ref(), token_lottery.key().as_ref(), token_lottery.round.to_le_bytes().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_record: Box<Account<'info, BuyerRecord>>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), 
+        ticket_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    /// CHECK: This account will be initialized by the metaplex program
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), 
+            ticket_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    /// CHECK: This account will be initialized by the metaplex program
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    /// CHECK: This account will be initialized by the metaplex program
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), 
+            collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    /// CHECK: This account will be initialized by the metaplex program
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_mint: InterfaceAccount<'info, Mint>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    pub payment: PaymentAccounts<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct BuyCompactTicket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Ticket::INIT_SPACE,
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), token_lottery.total_tickets.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + BuyerRecord::INIT_SPACE,
+        seeds = [b"buyer".as_ref(), token_lottery.key().as_ref(), token_lottery.round.to_le_bytes().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_record: Box<Account<'info, BuyerRecord>>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    pub payment: PaymentAccounts<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_buy_ticket(ctx: Context<BuyTicket>, quantity: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let first_ticket_id = ctx.accounts.token_lottery.total_tickets;
+    let ticket_name = NAME.to_owned() + first_ticket_id.to_string().as_str();
+
+    require!(
+        ctx.accounts.token_lottery.ticket_mode == TicketMode::Nft,
+        ErrorCode::InvalidTicketMode
+    );
+
+    let cost = record_purchase(
+        &mut ctx.accounts.token_lottery,
+        &mut ctx.accounts.ticket,
+        ctx.bumps.ticket,
+        &mut ctx.accounts.buyer_record,
+        ctx.accounts.payer.key(),
+        ctx.accounts.ticket_mint.key(),
+        quantity,
+        &clock,
+    )?;
+
+    // 1. チケット代金を支払う
+    collect_payment(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        &ctx.accounts.payment,
+        cost,
+    )?;
+
+    let lottery_id_bytes = ctx.accounts.token_lottery.lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id_bytes.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
+
+    // 2. チケットNFTをミント
+    let mint_to_accounts = MintTo {
+        mint: ctx.accounts.ticket_mint.to_account_info(),
+        to: ctx.accounts.destination.to_account_info(),
+        authority: ctx.accounts.collection_mint.to_account_info(),
+    };
+
+    let mint_to_cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        mint_to_accounts,
+    ).with_signer(&signer_seeds);
+
+    mint_to(
+        mint_to_cpi_context,
+        1,  // amount: ミントするトークン量（NFTなので1）
+    )?;
+
+    // 3. メタデータアカウントを作成
+    let create_metadata_accounts_v3_accounts = CreateMetadataAccountsV3 {
+        metadata: ctx.accounts.metadata.to_account_info(),
+        mint: ctx.accounts.ticket_mint.to_account_info(),
+        mint_authority: ctx.accounts.collection_mint.to_account_info(),
+        update_authority: ctx.accounts.collection_mint.to_account_info(),
+        payer: ctx.accounts.payer.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+
+    let data_v2 = DataV2 {
+        name: ticket_name,                  // チケット名（例: "Token Lottery Ticket #0"）
+        symbol: ctx.accounts.token_lottery.symbol.clone(), // NFTのシンボル
+        uri: ctx.accounts.token_lottery.ticket_uri(first_ticket_id), // 例: "{base_uri}/0.json"
+        seller_fee_basis_points: 0,         // ロイヤリティ（0%）
+        creators: None,                     // クリエイター情報（なし）
+        collection: None,                   // コレクションは後でset_and_verify_sized_collection_itemで設定
+        uses: None,                         // 使用回数制限（なし）
+    };
+
+    let create_metadata_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        create_metadata_accounts_v3_accounts,
+    ).with_signer(&signer_seeds);
+
+    create_metadata_accounts_v3(
+        create_metadata_cpi_context,
+        data_v2,
+        true,  // is_mutable: メタデータを後から更新可能にする
+        true,  // update_authority_is_signer: update_authorityが署名者である
+        None,  // collection_details: 通常のNFT（コレクションではない）
+    )?;
+
+    // 4. マスターエディションを作成
+    let create_master_edition_v3_accounts = CreateMasterEditionV3 {
+        payer: ctx.accounts.payer.to_account_info(),
+        mint: ctx.accounts.ticket_mint.to_account_info(),
+        edition: ctx.accounts.master_edition.to_account_info(),
+        mint_authority: ctx.accounts.collection_mint.to_account_info(),
+        update_authority: ctx.accounts.collection_mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+
+    let create_master_edition_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        create_master_edition_v3_accounts,
+    ).with_signer(&signer_seeds);
+
+    create_master_edition_v3(
+        create_master_edition_cpi_context,
+        Some(0),  // max_supply: 最大供給量（0 = 追加ミント不可）
+    )?;
+
+    // 5. NFTをコレクションに追加して検証
+    let set_and_verify_sized_collection_item_accounts = SetAndVerifySizedCollectionItem {
+        metadata: ctx.accounts.metadata.to_account_info(),
+        collection_authority: ctx.accounts.collection_mint.to_account_info(),
+        payer: ctx.accounts.payer.to_account_info(),
+        update_authority: ctx.accounts.collection_mint.to_account_info(),
+        collection_mint: ctx.accounts.collection_mint.to_account_info(),
+        collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+        collection_master_edition: ctx.accounts.collection_master_edition.to_account_info(),
+    };
+
+    let set_and_verify_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        set_and_verify_sized_collection_item_accounts,
+    ).with_signer(&signer_seeds);
+
+    set_and_verify_sized_collection_item(
+        set_and_verify_cpi_context,
+        None,  // collection_authority_record: コレクション権限の委譲レコード（なし）
+    )?;
+
+    Ok(())
+}
+
+pub fn process_buy_compact_ticket(ctx: Context<BuyCompactTicket>, quantity: u64) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        ctx.accounts.token_lottery.ticket_mode == TicketMode::Compact,
+        ErrorCode::InvalidTicketMode
+    );
+
+    let cost = record_purchase(
+        &mut ctx.accounts.token_lottery,
+        &mut ctx.accounts.ticket,
+        ctx.bumps.ticket,
+        &mut ctx.accounts.buyer_record,
+        ctx.accounts.payer.key(),
+        Pubkey::default(),
+        quantity,
+        &clock,
+    )?;
+
+    collect_payment(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        &ctx.accounts.payment,
+        cost,
+    )?;
+
+    msg!(
+        "Compact tickets {}..{} bought",
+        ctx.accounts.ticket.first_ticket_id,
+        ctx.accounts.ticket.first_ticket_id + quantity
+    );
+
+    Ok(())
+}
+
+/// チケット購入の検証と記録
+/// 販売期間・販売上限・ウォレットごとの上限を確認し、手数料を差し引いた額を賞金プールに積み立てる
+/// 戻り値は支払う代金の合計
+#[allow(clippy::too_many_arguments)]
+fn record_purchase(
+    token_lottery: &mut TokenLottery,
+    ticket: &mut Ticket,
+    ticket_bump: u8,
+    buyer_record: &mut BuyerRecord,
+    buyer: Pubkey,
+    ticket_mint: Pubkey,
+    quantity: u64,
+    clock: &Clock,
+) -> Result<u64> {
+    let now = token_lottery.now(clock);
+    require!(
+        now >= token_lottery.lottery_start && now <= token_lottery.lottery_end,
+        ErrorCode::LotteryNotOpen
+    );
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(quantity > 0, ErrorCode::InvalidQuantity);
+
+    // 販売上限とウォレットごとの上限を確認
+    let first_ticket_id = token_lottery.total_tickets;
+    let total_tickets = first_ticket_id
+        .checked_add(quantity)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        total_tickets - token_lottery.round_first_ticket_id <= token_lottery.max_tickets,
+        ErrorCode::MaxTicketsExceeded
+    );
+
+    buyer_record.owner = buyer;
+    buyer_record.round = token_lottery.round;
+    buyer_record.tickets_bought = buyer_record
+        .tickets_bought
+        .checked_add(quantity)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let max_per_wallet = token_lottery.max_tickets_per_wallet;
+    require!(
+        max_per_wallet == 0 || buyer_record.tickets_bought <= max_per_wallet,
+        ErrorCode::WalletTicketLimitExceeded
+    );
+
+    let cost = token_lottery.price
+        .checked_mul(quantity)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    match token_lottery.prize_mode {
+        PrizeMode::Pot => {
+            // 購入時に手数料を差し引き、残りを賞金プールに積み立てる
+            // 手数料は当選者が決まるまで引き出せない（中止された場合は返金する）
+            let fee = token_lottery.ticket_fee()? * quantity;
+            token_lottery.round_fees = token_lottery.round_fees
+                .checked_add(fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+            token_lottery.round_pot = token_lottery.round_pot
+                .checked_add(cost - fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+            token_lottery.lottery_pot_amount = token_lottery.lottery_pot_amount
+                .checked_add(cost - fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        PrizeMode::NoLoss => {
+            // 全額を預け入れとして扱う（手数料はharvest_yieldで利回りから差し引く）
+            token_lottery.deposits = token_lottery.deposits
+                .checked_add(cost)
+                .ok_or(ErrorCode::MathOverflow)?;
+            token_lottery.round_deposits = token_lottery.round_deposits
+                .checked_add(cost)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+    }
+
+    // チケットIDの範囲を記録（当選判定と返金で使う）
+    ticket.bump = ticket_bump;
+    ticket.owner = buyer;
+    ticket.first_ticket_id = first_ticket_id;
+    ticket.quantity = quantity;
+    ticket.mint = ticket_mint;
+    ticket.claimed_tiers = 0;
+    ticket.deposit = cost;
+
+    token_lottery.total_tickets = total_tickets;
+
+    emit!(TicketPurchased {
+        lottery_id: token_lottery.lottery_id,
+        round: token_lottery.round,
+        buyer,
+        ticket_id: first_ticket_id,
+        quantity,
+        mint: ticket_mint,
+        price: cost,
+    });
+
+    Ok(cost)
+}
@@ -0,0 +1,275 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use anchor_spl::metadata::{
+    Metadata,
+    MetadataAccount,
+    UpdateMetadataAccountsV2,
+    update_metadata_accounts_v2,
+    mpl_token_metadata::types::DataV2,
+};
+use crate::error::ErrorCode;
+use crate::events::PrizeClaimed;
+use crate::instructions::payment::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(tier: u8, first_ticket_id: u64)]
+pub struct RevealWinningTicket<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        seeds = [token_lottery.lottery_id.to_le_bytes().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier: u8, first_ticket_id: u64)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    #[account(
+        mut,
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        seeds = [token_lottery.lottery_id.to_le_bytes().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: Account<'info, MetadataAccount>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub collection_metadata: Account<'info, MetadataAccount>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    pub payment: PaymentAccounts<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier: u8, first_ticket_id: u64)]
+pub struct ClaimCompactPrize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.owner == payer.key() @ ErrorCode::IncorrectTicket,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    pub payment: PaymentAccounts<'info>,
+}
+
+pub fn process_reveal_winning_ticket(ctx: Context<RevealWinningTicket>, tier: u8, first_ticket_id: u64) -> Result<()> {
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    require!(token_lottery.ticket_mode == TicketMode::Nft, ErrorCode::InvalidTicketMode);
+    require!(token_lottery.winner_chosen, ErrorCode::WinnerNotChosen);
+    require!(
+        (tier as usize) < token_lottery.winning_ticket_ids.len(),
+        ErrorCode::InvalidTier
+    );
+    require!(
+        ctx.accounts.ticket.contains(token_lottery.winning_ticket_id(tier)),
+        ErrorCode::IncorrectTicket
+    );
+    require!(
+        ctx.accounts.ticket.mint == ctx.accounts.ticket_mint.key(),
+        ErrorCode::IncorrectTicket
+    );
+
+    // 名前・コレクションなどは現在のメタデータをそのまま引き継ぎ、URIだけを変更する
+    let metadata = &ctx.accounts.metadata;
+    let data_v2 = DataV2 {
+        name: metadata.name.trim_end_matches('\0').to_string(),
+        symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+        uri: token_lottery.winner_uri(first_ticket_id),  // 例: "{base_uri}/winner/0.json"
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: metadata.creators.clone(),
+        collection: metadata.collection.clone(),
+        uses: metadata.uses.clone(),
+    };
+
+    let lottery_id_bytes = token_lottery.lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id_bytes.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
+
+    let update_metadata_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        UpdateMetadataAccountsV2 {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            update_authority: ctx.accounts.collection_mint.to_account_info(),
+        },
+    ).with_signer(signer_seeds);
+
+    update_metadata_accounts_v2(
+        update_metadata_cpi_context,
+        None,           // new_update_authority: 変更しない
+        Some(data_v2),  // data: URIを変更したメタデータ
+        None,           // primary_sale_happened: 変更しない
+        None,           // is_mutable: 変更しない
+    )?;
+
+    msg!("Ticket {} (first id {}) revealed as tier {} winner", ctx.accounts.ticket_mint.key(), first_ticket_id, tier);
+
+    Ok(())
+}
+
+pub fn process_claim_prize(ctx: Context<ClaimPrize>, tier: u8, first_ticket_id: u64) -> Result<()> {
+    msg!("Winner chosen: {}", ctx.accounts.token_lottery.winner_chosen);
+    let prize = ctx.accounts.token_lottery.check_claim(tier, &ctx.accounts.ticket)?;
+
+    // Check if token is a part of the collection
+    let collection = ctx.accounts.metadata.collection.as_ref()
+        .ok_or(ErrorCode::NoCollection)?;
+    require!(collection.verified, ErrorCode::NotVerifiedTicket);
+    require!(collection.key == ctx.accounts.collection_mint.key(), ErrorCode::IncorrectTicket);
+
+    // 購入時に記録したチケットIDとミントの対応で当選チケットを確認する
+    // （メタデータの名前は比較しない）
+    require!(
+        ctx.accounts.ticket.mint == ctx.accounts.ticket_mint.key(),
+        ErrorCode::IncorrectTicket
+    );
+    // Check if the winner has the winning ticket
+    require!(ctx.accounts.destination.amount == 1, ErrorCode::IncorrectTicket);
+
+    msg!("Ticket {} (first id {})", ctx.accounts.ticket_mint.key(), first_ticket_id);
+
+    msg!("Tier {} prize: {}", tier, prize);
+
+    pay_out(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payment,
+        prize,
+    )?;
+
+    ctx.accounts.token_lottery.record_claim(tier, prize);
+    ctx.accounts.ticket.mark_claimed(tier);
+
+    emit!(PrizeClaimed {
+        lottery_id: ctx.accounts.token_lottery.lottery_id,
+        round: ctx.accounts.token_lottery.round,
+        winner: ctx.accounts.payer.key(),
+        tier,
+        ticket_id: ctx.accounts.token_lottery.winning_ticket_id(tier),
+        amount: prize,
+    });
+
+    Ok(())
+}
+
+pub fn process_claim_compact_prize(ctx: Context<ClaimCompactPrize>, tier: u8, _first_ticket_id: u64) -> Result<()> {
+    // NFTモードではNFTの保有者が所有者なので、Ticketアカウントの所有者では判定しない
+    require!(
+        ctx.accounts.token_lottery.ticket_mode == TicketMode::Compact,
+        ErrorCode::InvalidTicketMode
+    );
+    let prize = ctx.accounts.token_lottery.check_claim(tier, &ctx.accounts.ticket)?;
+    msg!("Tier {} prize: {}", tier, prize);
+
+    pay_out(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payment,
+        prize,
+    )?;
+
+    ctx.accounts.token_lottery.record_claim(tier, prize);
+    ctx.accounts.ticket.mark_claimed(tier);
+
+    emit!(PrizeClaimed {
+        lottery_id: ctx.accounts.token_lottery.lottery_id,
+        round: ctx.accounts.token_lottery.round,
+        winner: ctx.accounts.payer.key(),
+        tier,
+        ticket_id: ctx.accounts.token_lottery.winning_ticket_id(tier),
+        amount: prize,
+    });
+
+    Ok(())
+}
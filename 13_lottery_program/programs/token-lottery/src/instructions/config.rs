@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(lottery_id: u64)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TokenLottery::INIT_SPACE,
+        // lottery_idごとに別の抽選を同時に運用できる
+        seeds = [b"token_lottery".as_ref(), lottery_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+}
+
+pub fn process_initialize_config(
+    ctx: Context<InitializeConfig>,
+    lottery_id: u64,
+    config: LotteryConfig,
+) -> Result<()> {
+    validate_config(&config)?;
+
+    ctx.accounts.token_lottery.bump = ctx.bumps.token_lottery;
+    ctx.accounts.token_lottery.lottery_id = lottery_id;
+    // start / end をスロットとunixタイムスタンプのどちらで解釈するか
+    ctx.accounts.token_lottery.window_mode = config.window_mode;
+    ctx.accounts.token_lottery.lottery_start = config.start;
+    ctx.accounts.token_lottery.lottery_end = config.end;
+    ctx.accounts.token_lottery.price = config.price;
+    ctx.accounts.token_lottery.authority = ctx.accounts.payer.key();
+    ctx.accounts.token_lottery.randomness_account = Pubkey::default();
+
+    ctx.accounts.token_lottery.total_tickets = 0;
+    ctx.accounts.token_lottery.lottery_pot_amount = 0;
+    ctx.accounts.token_lottery.winner_chosen = false;
+    ctx.accounts.token_lottery.prize_tiers = config.prize_tiers;
+    ctx.accounts.token_lottery.winning_ticket_ids = Vec::new();
+    ctx.accounts.token_lottery.tier_claimed = Vec::new();
+    ctx.accounts.token_lottery.prize_pool = 0;
+    // Noneの場合はSOL（lamports）で支払う
+    ctx.accounts.token_lottery.payment_mint = config.payment_mint;
+    ctx.accounts.token_lottery.fee_bps = config.fee_bps;
+    ctx.accounts.token_lottery.fee_recipient = config.fee_recipient;
+    ctx.accounts.token_lottery.fees_collected = 0;
+    ctx.accounts.token_lottery.round_fees = 0;
+    ctx.accounts.token_lottery.round_pot = 0;
+    ctx.accounts.token_lottery.cancelled = false;
+    ctx.accounts.token_lottery.max_tickets = config.max_tickets;
+    // 0の場合はウォレットごとの上限なし
+    ctx.accounts.token_lottery.max_tickets_per_wallet = config.max_tickets_per_wallet;
+    ctx.accounts.token_lottery.ticket_mode = config.ticket_mode;
+    // reveal_winnerを呼び出したキーパーへの報酬（0の場合はなし）
+    ctx.accounts.token_lottery.keeper_tip = config.keeper_tip;
+    ctx.accounts.token_lottery.randomness_mode = config.randomness_mode;
+    ctx.accounts.token_lottery.seed_accumulator = [0; 32];
+    ctx.accounts.token_lottery.seeds_committed = 0;
+    ctx.accounts.token_lottery.seeds_revealed = 0;
+    ctx.accounts.token_lottery.round = 0;
+    ctx.accounts.token_lottery.round_first_ticket_id = 0;
+    ctx.accounts.token_lottery.draw_slot = 0;
+    // チケットのURIは {base_uri}/{ticket_id}.json
+    ctx.accounts.token_lottery.base_uri = config.base_uri.trim_end_matches('/').to_string();
+    ctx.accounts.token_lottery.symbol = config.symbol;
+    ctx.accounts.token_lottery.prize_mode = config.prize_mode;
+    ctx.accounts.token_lottery.deposits = 0;
+    ctx.accounts.token_lottery.round_deposits = 0;
+    ctx.accounts.token_lottery.yield_harvested = false;
+    Ok(())
+}
+
+pub fn process_update_config(ctx: Context<UpdateConfig>, config: LotteryConfig) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(
+        ctx.accounts.authority.key() == token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+    require!(
+        token_lottery.round_tickets() == 0 && !token_lottery.winner_chosen,
+        ErrorCode::TicketsAlreadySold
+    );
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    validate_config(&config)?;
+
+    // 支払い方法・手数料の受取人・チケット・乱数・賞金のモードとメタデータは変更できない
+    require!(
+        config.payment_mint == token_lottery.payment_mint
+            && config.fee_recipient == token_lottery.fee_recipient
+            && config.ticket_mode == token_lottery.ticket_mode
+            && config.randomness_mode == token_lottery.randomness_mode
+            && config.prize_mode == token_lottery.prize_mode
+            && config.base_uri.trim_end_matches('/') == token_lottery.base_uri
+            && config.symbol == token_lottery.symbol,
+        ErrorCode::ImmutableConfig
+    );
+
+    token_lottery.window_mode = config.window_mode;
+    token_lottery.lottery_start = config.start;
+    token_lottery.lottery_end = config.end;
+    token_lottery.price = config.price;
+    token_lottery.prize_tiers = config.prize_tiers;
+    token_lottery.fee_bps = config.fee_bps;
+    token_lottery.max_tickets = config.max_tickets;
+    token_lottery.max_tickets_per_wallet = config.max_tickets_per_wallet;
+    token_lottery.keeper_tip = config.keeper_tip;
+
+    Ok(())
+}
+
+/// initialize_config / update_config の設定値を検証する
+fn validate_config(config: &LotteryConfig) -> Result<()> {
+    require!(config.start < config.end, ErrorCode::InvalidLotteryWindow);
+    require!(config.price > 0, ErrorCode::InvalidPrice);
+    // 賞金の配分（basis points）: 例 [5000, 3000, 2000] = 1等50%、2等30%、3等20%
+    let prize_tiers = &config.prize_tiers;
+    require!(
+        !prize_tiers.is_empty() && prize_tiers.len() <= MAX_PRIZE_TIERS as usize,
+        ErrorCode::InvalidPrizeTiers
+    );
+    require!(prize_tiers.iter().all(|&bps| bps > 0), ErrorCode::InvalidPrizeTiers);
+    require!(
+        prize_tiers.iter().map(|&bps| bps as u32).sum::<u32>() == BASIS_POINTS as u32,
+        ErrorCode::InvalidPrizeTiers
+    );
+    // 手数料は売上の一部のみ（100%は不可）
+    require!(config.fee_bps < BASIS_POINTS, ErrorCode::InvalidFee);
+    require!(config.max_tickets > 0, ErrorCode::InvalidMaxTickets);
+    // キーパーへの報酬はチケット1枚の価格の一部まで
+    let max_keeper_tip = (config.price as u128)
+        .checked_mul(MAX_KEEPER_TIP_BPS as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / BASIS_POINTS as u128;
+    require!(config.keeper_tip as u128 <= max_keeper_tip, ErrorCode::InvalidKeeperTip);
+    require!(
+        config.base_uri.len() <= MAX_BASE_URI_LENGTH as usize
+            && config.symbol.len() <= MAX_SYMBOL_LENGTH as usize,
+        ErrorCode::InvalidMetadata
+    );
+    // NoLossモードの利回りはSOLで受け取るため、SOL払いのみ
+    require!(
+        config.prize_mode == PrizeMode::Pot || config.payment_mint.is_none(),
+        ErrorCode::InvalidPrizeMode
+    );
+    Ok(())
+}
@@ -0,0 +1,360 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::events::{WinnerCommitted, WinnerRevealed};
+use crate::instructions::payment::*;
+use crate::randomness::with_randomness;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CommitWinner<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// CHECK: The account's data is validated manually within the handler.
+    pub randomness_account_data: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealWinner<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// CHECK: The account's data is validated manually within the handler.
+    pub randomness_account_data: UncheckedAccount<'info>,
+
+    // トークン払いの抽選でkeeper_tipを受け取る場合のみ必要
+    pub payment: PaymentAccounts<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_commit_winner(ctx: Context<CommitWinner>) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(
+        token_lottery.randomness_mode == RandomnessMode::Oracle,
+        ErrorCode::InvalidRandomnessMode
+    );
+    require!(
+        token_lottery.now(&clock) >= token_lottery.lottery_end,
+        ErrorCode::LotteryNotCompleted
+    );
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    // 結果を見てから別のランダムネスでやり直せないよう、コミットは1回だけ
+    // （乱数が解決されない場合はcancel_lotteryで中止する）
+    require!(
+        token_lottery.randomness_account == Pubkey::default(),
+        ErrorCode::RandomnessAlreadyCommitted
+    );
+
+    let seed_slot = with_randomness(&ctx.accounts.randomness_account_data, |randomness| {
+        Ok(randomness.seed_slot())
+    })?;
+
+    let previous_slot = clock.slot.checked_sub(1).ok_or(ErrorCode::MathOverflow)?;
+    require!(seed_slot == previous_slot, ErrorCode::RandomnessAlreadyRevealed);
+
+    token_lottery.randomness_account = ctx.accounts.randomness_account_data.key();
+
+    emit!(WinnerCommitted {
+        lottery_id: token_lottery.lottery_id,
+        round: token_lottery.round,
+        randomness_account: token_lottery.randomness_account,
+        seed_slot,
+    });
+
+    Ok(())
+}
+
+pub fn process_reveal_winner(ctx: Context<RevealWinner>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    {
+        let token_lottery = &ctx.accounts.token_lottery;
+        require!(
+            token_lottery.randomness_mode == RandomnessMode::Oracle,
+            ErrorCode::InvalidRandomnessMode
+        );
+        require!(
+            ctx.accounts.randomness_account_data.key() == token_lottery.randomness_account,
+            ErrorCode::IncorrectRandomnessAccount
+        );
+        require!(
+            token_lottery.now(&clock) >= token_lottery.lottery_end,
+            ErrorCode::LotteryNotCompleted
+        );
+    }
+
+    let revealed_random_value = with_randomness(&ctx.accounts.randomness_account_data, |randomness| {
+        randomness.get_value(&clock)
+    })?;
+
+    draw_winners(
+        &mut ctx.accounts.token_lottery,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payment,
+        revealed_random_value,
+    )
+}
+
+/// 乱数から当選者を決める（reveal_winner / reveal_winner_from_seeds 共通）
+/// 呼び出し元にはkeeper_tipが賞金プールから支払われる
+pub(crate) fn draw_winners<'info>(
+    token_lottery: &mut Account<'info, TokenLottery>,
+    keeper: &AccountInfo<'info>,
+    payment: &PaymentAccounts<'info>,
+    random_value: [u8; 32],
+) -> Result<()> {
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    let round_tickets = token_lottery.round_tickets();
+    require!(round_tickets > 0, ErrorCode::NoTicketsSold);
+    // NoLossモードの賞金は利回りのみなので、先に利回りを受け取る
+    require!(
+        token_lottery.prize_mode == PrizeMode::Pot || token_lottery.yield_harvested,
+        ErrorCode::YieldNotHarvested
+    );
+
+    // キーパーへの報酬（賞金プールを超えない）
+    let tip = token_lottery.keeper_tip.min(token_lottery.lottery_pot_amount);
+    if tip > 0 {
+        pay_out(token_lottery, keeper, payment, tip)?;
+        token_lottery.lottery_pot_amount -= tip;
+        msg!("Keeper tip: {}", tip);
+    }
+
+    msg!("Randomness result: {:?}", random_value);
+    msg!("Ticket num: {}", round_tickets);
+
+    // チケット数が賞の数より少ない場合は、上位の賞から順に当選者を決める
+    // チケットIDはラウンドをまたいで通し番号なので、このラウンドの先頭IDを足す
+    let num_winners = (token_lottery.prize_tiers.len() as u64)
+        .min(round_tickets) as usize;
+    let winning_ticket_ids: Vec<u64> =
+        select_winners(&random_value, round_tickets, num_winners)
+            .into_iter()
+            .map(|index| token_lottery.round_first_ticket_id + index)
+            .collect();
+
+    msg!("Winners: {:?}", winning_ticket_ids);
+
+    emit!(WinnerRevealed {
+        lottery_id: token_lottery.lottery_id,
+        round: token_lottery.round,
+        random_value,
+        total_tickets: round_tickets,
+        round_first_ticket_id: token_lottery.round_first_ticket_id,
+        winning_ticket_ids: winning_ticket_ids.clone(),
+        prize_pool: token_lottery.lottery_pot_amount,
+    });
+
+    token_lottery.tier_claimed = vec![false; winning_ticket_ids.len()];
+    token_lottery.winning_ticket_ids = winning_ticket_ids;
+    token_lottery.prize_pool = token_lottery.lottery_pot_amount;
+    token_lottery.winner_chosen = true;
+    // 当選者が決まったので、このラウンドの手数料は返金されない
+    token_lottery.fees_collected = token_lottery.fees_collected
+        .checked_add(token_lottery.round_fees)
+        .ok_or(ErrorCode::MathOverflow)?;
+    token_lottery.round_fees = 0;
+    token_lottery.draw_slot = Clock::get()?.slot;
+
+    Ok(())
+}
+
+/// 32バイトの乱数から [0, total_tickets) の当選チケットIDを選ぶ
+///
+/// 先頭128ビットをu128として読み、total_ticketsとの幅広乗算の上位ビットを使う
+/// (floor(r * n / 2^128))。剰余演算と違い全てのチケットが当選可能で、
+/// 偏りは n / 2^128 以下に抑えられる。
+pub fn select_winner_index(random_value: &[u8; 32], total_tickets: u64) -> u64 {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&random_value[..16]);
+    let r = u128::from_le_bytes(bytes);
+
+    // r * n を 2^64 単位に分けて計算し、上位128ビットを求める
+    let n = total_tickets as u128;
+    let hi = (r >> 64) * n;
+    let lo = (r & u64::MAX as u128) * n;
+
+    ((hi + (lo >> 64)) >> 64) as u64
+}
+
+/// 乱数から重複のない当選チケットIDを num_winners 個選ぶ（非復元抽出）
+///
+/// k番目の抽選では、まだ選ばれていない total_tickets - k 枚の中から
+/// select_winner_index と同じ幅広乗算で1枚を選ぶ。乗算の下位128ビット
+/// （小数部）を次の抽選の乱数として使うので、1つ目の当選者は
+/// select_winner_index の結果と一致し、棄却による選び直しも発生しない。
+pub fn select_winners(random_value: &[u8; 32], total_tickets: u64, num_winners: usize) -> Vec<u64> {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&random_value[..16]);
+    let mut r = u128::from_le_bytes(bytes);
+
+    let num_winners = (num_winners as u64).min(total_tickets);
+    let mut winners: Vec<u64> = Vec::with_capacity(num_winners as usize);
+    // 選ばれたIDを昇順で保持する
+    let mut selected: Vec<u64> = Vec::with_capacity(num_winners as usize);
+
+    for k in 0..num_winners {
+        let remaining = (total_tickets - k) as u128;
+        let hi = (r >> 64) * remaining;
+        let lo = (r & u64::MAX as u128) * remaining;
+        let index = ((hi + (lo >> 64)) >> 64) as u64;
+        r = r.wrapping_mul(remaining);
+
+        // 未選択のチケットの中で index 番目のIDに変換する
+        let mut ticket_id = index;
+        for &id in selected.iter() {
+            if id <= ticket_id {
+                ticket_id += 1;
+            }
+        }
+
+        let pos = selected.partition_point(|&id| id < ticket_id);
+        selected.insert(pos, ticket_id);
+        winners.push(ticket_id);
+    }
+
+    winners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テスト用の決定的な乱数列（splitmix64）
+    fn random_values(seed: u64, count: usize) -> Vec<[u8; 32]> {
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+
+        (0..count)
+            .map(|_| {
+                let mut value = [0u8; 32];
+                for chunk in value.chunks_mut(8) {
+                    chunk.copy_from_slice(&next().to_le_bytes());
+                }
+                value
+            })
+            .collect()
+    }
+
+    /// 各チケットの当選回数のカイ二乗統計量
+    fn chi_square(total_tickets: u64, samples: &[[u8; 32]]) -> f64 {
+        let mut counts = vec![0u64; total_tickets as usize];
+        for value in samples {
+            counts[select_winner_index(value, total_tickets) as usize] += 1;
+        }
+
+        let expected = samples.len() as f64 / total_tickets as f64;
+        counts
+            .iter()
+            .map(|&count| {
+                let diff = count as f64 - expected;
+                diff * diff / expected
+            })
+            .sum()
+    }
+
+    #[test]
+    fn winner_index_is_within_range() {
+        assert_eq!(select_winner_index(&[0u8; 32], 1_000), 0);
+        assert_eq!(select_winner_index(&[0xFF; 32], 1_000), 999);
+        assert_eq!(select_winner_index(&[0xFF; 32], u64::MAX), u64::MAX - 1);
+
+        for value in random_values(1, 1_000) {
+            assert_eq!(select_winner_index(&value, 1), 0);
+            assert!(select_winner_index(&value, 7) < 7);
+        }
+    }
+
+    #[test]
+    fn winner_index_uses_more_than_one_byte() {
+        // 先頭バイトが同じでも、残りのビットによって結果が変わる
+        let mut low = [0u8; 32];
+        let mut high = [0u8; 32];
+        low[0] = 0x42;
+        high[0] = 0x42;
+        high[15] = 0x80;
+
+        assert_ne!(select_winner_index(&low, 1_000), select_winner_index(&high, 1_000));
+    }
+
+    #[test]
+    fn every_ticket_can_win_with_more_than_256_tickets() {
+        let total_tickets = 1_000;
+        let mut seen = vec![false; total_tickets as usize];
+        for value in random_values(2, 50_000) {
+            seen[select_winner_index(&value, total_tickets) as usize] = true;
+        }
+
+        assert!(seen.iter().all(|&won| won));
+    }
+
+    #[test]
+    fn winners_are_distinct_and_in_range() {
+        for value in random_values(7, 1_000) {
+            let winners = select_winners(&value, 20, 5);
+            assert_eq!(winners.len(), 5);
+            assert!(winners.iter().all(|&id| id < 20));
+            for (i, id) in winners.iter().enumerate() {
+                assert!(!winners[i + 1..].contains(id));
+            }
+            // 1等は単一当選者の場合と同じ選び方
+            assert_eq!(winners[0], select_winner_index(&value, 20));
+        }
+    }
+
+    #[test]
+    fn winners_cover_all_tickets_when_tickets_equal_tiers() {
+        for value in random_values(8, 100) {
+            let mut winners = select_winners(&value, 3, 3);
+            winners.sort();
+            assert_eq!(winners, vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn winner_distribution_is_uniform() {
+        // 自由度 k - 1 のカイ二乗分布の平均は k - 1、標準偏差は sqrt(2(k - 1))
+        // 平均 + 5σ を超えなければ一様とみなす
+        for (seed, total_tickets) in [(3, 3u64), (4, 10), (5, 257), (6, 1_000)] {
+            let samples = random_values(seed, 200_000);
+            let dof = (total_tickets - 1) as f64;
+            let threshold = dof + 5.0 * (2.0 * dof).sqrt();
+
+            let statistic = chi_square(total_tickets, &samples);
+            assert!(
+                statistic < threshold,
+                "tickets={} chi2={} threshold={}",
+                total_tickets,
+                statistic,
+                threshold
+            );
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
+};
+use anchor_spl::metadata::{
+    Metadata,
+    CreateMetadataAccountsV3,
+    CreateMasterEditionV3,
+    SignMetadata,
+    create_master_edition_v3,
+    create_metadata_accounts_v3,
+    sign_metadata,
+    mpl_token_metadata::types::{
+            CollectionDetails,
+            Creator, 
+            DataV2,
+        },
+};
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(lottery_id: u64)]
+pub struct InitializePot<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_lottery".as_ref(), lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(mint::token_program = payment_token_program)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = payment_mint,
+        token::authority = token_lottery,
+        token::token_program = payment_token_program,
+        seeds = [b"pot".as_ref(), lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pot: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub payment_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(lottery_id: u64)]
+pub struct InitializeLottery<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_lottery".as_ref(), lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = collection_mint,
+        mint::freeze_authority = collection_mint,
+        seeds = [b"collection_mint".as_ref(), lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: This account will be initialized by the metaplex program
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: This account will be initialized by the metaplex program
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"collection_token_account".as_ref(), lottery_id.to_le_bytes().as_ref()],
+        bump,
+        token::mint = collection_mint,
+        token::authority = collection_token_account
+    )]
+    pub collection_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn process_initialize_pot(ctx: Context<InitializePot>, _lottery_id: u64) -> Result<()> {
+    require!(
+        ctx.accounts.payer.key() == ctx.accounts.token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+    require!(
+        ctx.accounts.token_lottery.payment_mint == Some(ctx.accounts.payment_mint.key()),
+        ErrorCode::InvalidPaymentMint
+    );
+
+    msg!("Pot created: {}", ctx.accounts.pot.key());
+    Ok(())
+}
+
+pub fn process_initialize_lottery(ctx: Context<InitializeLottery>, lottery_id: u64) -> Result<()> {
+    require!(
+        ctx.accounts.payer.key() == ctx.accounts.token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+    // コレクションNFTはNFTモードでのみ必要
+    require!(
+        ctx.accounts.token_lottery.ticket_mode == TicketMode::Nft,
+        ErrorCode::InvalidTicketMode
+    );
+
+    let lottery_id_bytes = lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id_bytes.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
+
+    // 1. コレクションNFTをミント
+    msg!("Creating mint accounts");
+
+    let mint_to_accounts = MintTo {
+        mint: ctx.accounts.collection_mint.to_account_info(),
+        to: ctx.accounts.collection_token_account.to_account_info(),
+        authority: ctx.accounts.collection_mint.to_account_info(),
+    };
+
+    let mint_to_cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        mint_to_accounts,
+    ).with_signer(&signer_seeds);
+
+    mint_to(
+        mint_to_cpi_context,
+        1,  // amount: ミントするトークン量（NFTなので1）
+    )?;
+
+    // 2. メタデータアカウントを作成
+    msg!("Creating metadata accounts");
+    let create_metadata_accounts_v3_accounts = CreateMetadataAccountsV3 {
+        metadata: ctx.accounts.metadata.to_account_info(),
+        mint: ctx.accounts.collection_mint.to_account_info(),
+        mint_authority: ctx.accounts.collection_mint.to_account_info(),
+        update_authority: ctx.accounts.collection_mint.to_account_info(),
+        payer: ctx.accounts.payer.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+
+    let data_v2 = DataV2 {
+        name: NAME.to_string(),             // NFTの名前
+        symbol: ctx.accounts.token_lottery.symbol.clone(), // NFTのシンボル
+        uri: ctx.accounts.token_lottery.collection_uri(),  // メタデータJSONのURI
+        seller_fee_basis_points: 0,         // ロイヤリティ（0 = 0%、10000 = 100%）
+        creators: Some(vec![Creator {
+            address: ctx.accounts.collection_mint.key(),
+            verified: false,                // 署名で検証済みかどうか
+            share: 100,                     // ロイヤリティの分配割合（合計100）
+        }]),
+        collection: None,                   // 所属するコレクション（これ自体がコレクションなのでNone）
+        uses: None,                         // 使用回数制限（なし）
+    };
+
+    let create_metadata_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        create_metadata_accounts_v3_accounts,
+    ).with_signer(&signer_seeds);
+
+    create_metadata_accounts_v3(
+        create_metadata_cpi_context,
+        data_v2,
+        true,  // is_mutable: メタデータを後から更新可能にする
+        true,  // update_authority_is_signer: update_authorityが署名者である
+        Some(CollectionDetails::V1 { size: 0 }), // collection_details: コレクションNFTとして設定
+    )?;
+
+    // 3. マスターエディションを作成
+    {
+    msg!("Creating Master edition accounts");
+    let create_master_edition_v3_accounts = CreateMasterEditionV3 {
+        payer: ctx.accounts.payer.to_account_info(),
+        mint: ctx.accounts.collection_mint.to_account_info(),
+        edition: ctx.accounts.master_edition.to_account_info(),
+        mint_authority: ctx.accounts.collection_mint.to_account_info(),
+        update_authority: ctx.accounts.collection_mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+
+    let create_master_edition_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        create_master_edition_v3_accounts,
+    ).with_signer(&signer_seeds);
+
+    create_master_edition_v3(
+        create_master_edition_cpi_context,
+        Some(0),  // max_supply: 最大供給量（0 = 追加ミント不可、None = 無制限）
+    )?;
+    }
+    // 4. クリエイターとして署名（コレクションを検証）
+    msg!("Verifying collection");
+    
+    let sign_metadata_accounts = SignMetadata {
+        creator: ctx.accounts.collection_mint.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+    };
+
+    let sign_metadata_cpi_context = CpiContext::new(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        sign_metadata_accounts,
+    ).with_signer(&signer_seeds);
+
+    sign_metadata(sign_metadata_cpi_context)?;
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::randomness::MockRandomness;
+
+#[derive(Accounts)]
+pub struct InitMockRandomness<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MockRandomness::INIT_SPACE,
+    )]
+    pub mock_randomness: Account<'info, MockRandomness>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMockRandomness<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = mock_randomness.authority == payer.key() @ ErrorCode::NotAuthorized,
+    )]
+    pub mock_randomness: Account<'info, MockRandomness>,
+}
+
+pub fn process_init_mock_randomness(ctx: Context<InitMockRandomness>) -> Result<()> {
+    let mock_randomness = &mut ctx.accounts.mock_randomness;
+    mock_randomness.authority = ctx.accounts.payer.key();
+    // Switchboardのコミットと同様に直前のスロットを記録するので、
+    // 同じトランザクションでcommit_winnerを呼び出せる
+    mock_randomness.seed_slot = Clock::get()?.slot.saturating_sub(1);
+    mock_randomness.value = [0; 32];
+    mock_randomness.revealed = false;
+    Ok(())
+}
+
+pub fn process_set_mock_randomness(
+    ctx: Context<SetMockRandomness>,
+    seed_slot: u64,
+    value: Option<[u8; 32]>,
+) -> Result<()> {
+    let mock_randomness = &mut ctx.accounts.mock_randomness;
+    mock_randomness.seed_slot = seed_slot;
+    mock_randomness.revealed = value.is_some();
+    mock_randomness.value = value.unwrap_or_default();
+    Ok(())
+}
@@ -0,0 +1,24 @@
+pub use config::*;
+pub mod config;
+pub use initialize::*;
+pub mod initialize;
+pub use yield_source::*;
+pub mod yield_source;
+pub use buy::*;
+pub mod buy;
+pub use draw::*;
+pub mod draw;
+pub use seed::*;
+pub mod seed;
+pub use claim::*;
+pub mod claim;
+pub use round::*;
+pub mod round;
+pub use refund::*;
+pub mod refund;
+pub use payment::*;
+pub mod payment;
+#[cfg(feature = "mock-randomness")]
+pub use mock_randomness::*;
+#[cfg(feature = "mock-randomness")]
+pub mod mock_randomness;
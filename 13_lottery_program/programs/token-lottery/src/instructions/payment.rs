@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::error::ErrorCode;
+use crate::state::*;
+
+/// トークン払いの抽選でのみ必要なアカウント（SOL払いの場合は全て省略する）
+/// token_lotteryとの対応はcollect_payment / pay_outでまとめて検証する
+#[derive(Accounts)]
+pub struct PaymentAccounts<'info> {
+    #[account(mint::token_program = payment_token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// 支払う側（購入者）または受け取る側（当選者・キーパー・手数料の受取人）のトークンアカウント
+    /// 受け取る側の場合、所有者はpay_outで確認する
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::token_program = payment_token_program,
+    )]
+    pub payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub pot: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// 検証済みのトークン払いのアカウント
+struct TokenPayment<'a, 'info> {
+    mint: &'a InterfaceAccount<'info, Mint>,
+    account: &'a InterfaceAccount<'info, TokenAccount>,
+    pot: &'a InterfaceAccount<'info, TokenAccount>,
+    token_program: &'a Interface<'info, TokenInterface>,
+}
+
+/// トークン払いのアカウントが揃っていて、token_lotteryの支払いミントとpotに一致することを確認する
+fn token_payment<'a, 'info>(
+    token_lottery: &Account<'info, TokenLottery>,
+    payment: &'a PaymentAccounts<'info>,
+) -> Result<TokenPayment<'a, 'info>> {
+    let (Some(mint), Some(account), Some(pot), Some(token_program)) = (
+        &payment.payment_mint,
+        &payment.payment_account,
+        &payment.pot,
+        &payment.payment_token_program,
+    ) else {
+        return err!(ErrorCode::MissingPaymentAccounts);
+    };
+
+    require!(
+        token_lottery.payment_mint == Some(mint.key()),
+        ErrorCode::InvalidPaymentMint
+    );
+    let (pot_address, _) = Pubkey::find_program_address(
+        &[b"pot".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(pot.key(), pot_address, ErrorCode::InvalidPot);
+
+    Ok(TokenPayment { mint, account, pot, token_program })
+}
+
+/// チケット代金をpayerから受け取る
+/// SOL払いの場合はtoken_lotteryへ、トークン払いの場合はpotへ送金する
+pub(crate) fn collect_payment<'info>(
+    token_lottery: &Account<'info, TokenLottery>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    payment: &PaymentAccounts<'info>,
+    amount: u64,
+) -> Result<()> {
+    if token_lottery.payment_mint.is_none() {
+        let transfer_accounts = Transfer {
+            from: payer.to_account_info(),
+            to: token_lottery.to_account_info(),
+        };
+
+        let transfer_cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            transfer_accounts,
+        );
+
+        return transfer(transfer_cpi_context, amount);
+    }
+
+    let token_payment = token_payment(token_lottery, payment)?;
+
+    let transfer_accounts = TransferChecked {
+        from: token_payment.account.to_account_info(),
+        mint: token_payment.mint.to_account_info(),
+        to: token_payment.pot.to_account_info(),
+        authority: payer.to_account_info(),
+    };
+
+    let transfer_cpi_context = CpiContext::new(
+        token_payment.token_program.to_account_info(),
+        transfer_accounts,
+    );
+
+    transfer_checked(transfer_cpi_context, amount, token_payment.mint.decimals)
+}
+
+/// 賞金・手数料・返金を支払う
+/// SOL払いの場合はtoken_lotteryからrecipientへ、トークン払いの場合はpotから
+/// recipientが所有するpayment_accountへ送金する
+pub(crate) fn pay_out<'info>(
+    token_lottery: &Account<'info, TokenLottery>,
+    recipient: &AccountInfo<'info>,
+    payment: &PaymentAccounts<'info>,
+    amount: u64,
+) -> Result<()> {
+    if token_lottery.payment_mint.is_none() {
+        // token_lotteryはデータを持つPDAなので、System Programのtransferではなく
+        // プログラムが直接lamportsを移動する
+        token_lottery.sub_lamports(amount)?;
+        recipient.add_lamports(amount)?;
+        return Ok(());
+    }
+
+    let token_payment = token_payment(token_lottery, payment)?;
+    require_keys_eq!(
+        token_payment.account.owner,
+        recipient.key(),
+        ErrorCode::InvalidPaymentAccount
+    );
+
+    transfer_from_pot(
+        token_lottery,
+        token_payment.pot,
+        token_payment.mint,
+        token_payment.account,
+        token_payment.token_program,
+        amount,
+    )
+}
+
+/// token_lotteryが署名して、トークン払いの抽選のpotから送金する
+fn transfer_from_pot<'info>(
+    token_lottery: &Account<'info, TokenLottery>,
+    pot: &InterfaceAccount<'info, TokenAccount>,
+    payment_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    payment_token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    let lottery_id_bytes = token_lottery.lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_lottery".as_ref(),
+        lottery_id_bytes.as_ref(),
+        &[token_lottery.bump],
+    ]];
+
+    let transfer_accounts = TransferChecked {
+        from: pot.to_account_info(),
+        mint: payment_mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: token_lottery.to_account_info(),
+    };
+
+    let transfer_cpi_context = CpiContext::new_with_signer(
+        payment_token_program.to_account_info(),
+        transfer_accounts,
+        signer_seeds,
+    );
+
+    transfer_checked(transfer_cpi_context, amount, payment_mint.decimals)
+}
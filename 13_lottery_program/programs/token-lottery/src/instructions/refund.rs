@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface};
+use crate::error::ErrorCode;
+use crate::instructions::payment::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(first_ticket_id: u64)]
+pub struct RefundTicket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        seeds = [token_lottery.lottery_id.to_le_bytes().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    pub payment: PaymentAccounts<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(first_ticket_id: u64)]
+pub struct RefundCompactTicket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"ticket".as_ref(), token_lottery.key().as_ref(), first_ticket_id.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.owner == payer.key() @ ErrorCode::IncorrectTicket,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    pub payment: PaymentAccounts<'info>,
+}
+
+pub fn process_refund_ticket(ctx: Context<RefundTicket>, first_ticket_id: u64) -> Result<()> {
+    let (price, fee) = ctx.accounts.token_lottery.check_refund(&ctx.accounts.ticket)?;
+    require!(ctx.accounts.destination.amount > 0, ErrorCode::IncorrectTicket);
+
+    // 1. チケットNFTをburn（同じチケットで二重に返金できないようにする）
+    let burn_accounts = Burn {
+        mint: ctx.accounts.ticket_mint.to_account_info(),
+        from: ctx.accounts.destination.to_account_info(),
+        authority: ctx.accounts.payer.to_account_info(),
+    };
+
+    burn(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), burn_accounts),
+        1,
+    )?;
+
+    // 2. チケット代金を返金（購入時に差し引いた手数料も含む）
+    let quantity = ctx.accounts.ticket.quantity;
+
+    pay_out(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payment,
+        price,
+    )?;
+
+    ctx.accounts.token_lottery.record_refund(&ctx.accounts.ticket, price, fee)?;
+
+    msg!("Refunded tickets {}..{}: {}", first_ticket_id, first_ticket_id + quantity, price);
+
+    Ok(())
+}
+
+pub fn process_refund_compact_ticket(ctx: Context<RefundCompactTicket>, first_ticket_id: u64) -> Result<()> {
+    // NFTモードではNFTの保有者が所有者なので、Ticketアカウントの所有者では判定しない
+    require!(
+        ctx.accounts.token_lottery.ticket_mode == TicketMode::Compact,
+        ErrorCode::InvalidTicketMode
+    );
+    let (price, fee) = ctx.accounts.token_lottery.check_refund(&ctx.accounts.ticket)?;
+    let quantity = ctx.accounts.ticket.quantity;
+
+    pay_out(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payment,
+        price,
+    )?;
+
+    ctx.accounts.token_lottery.record_refund(&ctx.accounts.ticket, price, fee)?;
+
+    msg!("Refunded tickets {}..{}: {}", first_ticket_id, first_ticket_id + quantity, price);
+
+    Ok(())
+}
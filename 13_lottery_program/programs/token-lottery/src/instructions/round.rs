@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::instructions::payment::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    /// CHECK: token_lotteryに設定された手数料の受取人（SOL払いの送金先）
+    #[account(mut, address = token_lottery.fee_recipient @ ErrorCode::InvalidFeeRecipient)]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    // payment_accountはfee_recipientが所有するトークンアカウント
+    pub payment: PaymentAccounts<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCancelledPot<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    // トークン払いの抽選でのみ必要（SOL払いの場合は省略する）
+    // payment_accountはauthorityが所有するトークンアカウント
+    pub payment: PaymentAccounts<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StartNextRound<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RoundResult::INIT_SPACE,
+        seeds = [b"round_result".as_ref(), token_lottery.key().as_ref(), token_lottery.round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub round_result: Box<Account<'info, RoundResult>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLottery<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+pub fn process_withdraw_fees(ctx: Context<WithdrawFees>) -> Result<()> {
+    require!(
+        ctx.accounts.authority.key() == ctx.accounts.token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+
+    // 現在のラウンドの手数料（round_fees）は中止された場合に返金するので含まない
+    // 当選者が決まったラウンドの手数料は、後のラウンドが中止されても引き出せる
+    let amount = ctx.accounts.token_lottery.fees_collected;
+    require!(amount > 0, ErrorCode::NoFeesToWithdraw);
+
+    pay_out(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.fee_recipient.to_account_info(),
+        &ctx.accounts.payment,
+        amount,
+    )?;
+
+    ctx.accounts.token_lottery.fees_collected = 0;
+
+    Ok(())
+}
+
+pub fn process_withdraw_cancelled_pot(ctx: Context<WithdrawCancelledPot>) -> Result<()> {
+    require!(
+        ctx.accounts.authority.key() == ctx.accounts.token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+    require!(ctx.accounts.token_lottery.cancelled, ErrorCode::LotteryNotCancelled);
+
+    // 現在のラウンドのチケット代金（round_pot）は返金に残し、
+    // 前のラウンドから繰り越した賞金と利回りだけを引き出す
+    let amount = ctx.accounts.token_lottery.lottery_pot_amount
+        .checked_sub(ctx.accounts.token_lottery.round_pot)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(amount > 0, ErrorCode::NoPotToWithdraw);
+
+    pay_out(
+        &ctx.accounts.token_lottery,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.payment,
+        amount,
+    )?;
+
+    ctx.accounts.token_lottery.lottery_pot_amount -= amount;
+    msg!("Withdrew {} from cancelled lottery {}", amount, ctx.accounts.token_lottery.lottery_id);
+
+    Ok(())
+}
+
+pub fn process_start_next_round(ctx: Context<StartNextRound>, start: u64, end: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(
+        ctx.accounts.payer.key() == token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+    require!(start < end, ErrorCode::InvalidLotteryWindow);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+
+    if token_lottery.winner_chosen {
+        // 全ての賞が受け取られるか、受け取り期限を過ぎるまで待つ
+        let all_claimed = token_lottery.tier_claimed.iter().all(|&claimed| claimed);
+        require!(
+            all_claimed ||
+            clock.slot > token_lottery.draw_slot.saturating_add(CLAIM_WINDOW_SLOTS),
+            ErrorCode::RoundNotFinished
+        );
+    } else {
+        // チケットが1枚も売れずに販売期間が終わった場合
+        require!(
+            token_lottery.round_tickets() == 0 &&
+            token_lottery.now(&clock) > token_lottery.lottery_end,
+            ErrorCode::RoundNotFinished
+        );
+    }
+
+    let rolled_over = token_lottery.lottery_pot_amount;
+
+    let round_result = &mut ctx.accounts.round_result;
+    round_result.lottery_id = token_lottery.lottery_id;
+    round_result.round = token_lottery.round;
+    round_result.first_ticket_id = token_lottery.round_first_ticket_id;
+    round_result.total_tickets = token_lottery.round_tickets();
+    round_result.winning_ticket_ids = token_lottery.winning_ticket_ids.clone();
+    round_result.tier_claimed = token_lottery.tier_claimed.clone();
+    round_result.prize_pool = token_lottery.prize_pool;
+    round_result.rolled_over = rolled_over;
+
+    msg!("Round {} finished, rolled over: {}", token_lottery.round, rolled_over);
+
+    // 次のラウンドを開始（lottery_pot_amountはそのまま繰り越す）
+    token_lottery.round += 1;
+    token_lottery.round_first_ticket_id = token_lottery.total_tickets;
+    token_lottery.lottery_start = start;
+    token_lottery.lottery_end = end;
+    token_lottery.winner_chosen = false;
+    token_lottery.winning_ticket_ids = Vec::new();
+    token_lottery.tier_claimed = Vec::new();
+    token_lottery.prize_pool = 0;
+    token_lottery.round_fees = 0;
+    token_lottery.round_pot = 0;
+    token_lottery.draw_slot = 0;
+    token_lottery.randomness_account = Pubkey::default();
+    token_lottery.seed_accumulator = [0; 32];
+    token_lottery.seeds_committed = 0;
+    token_lottery.seeds_revealed = 0;
+    token_lottery.round_deposits = 0;
+    token_lottery.yield_harvested = false;
+
+    Ok(())
+}
+
+pub fn process_cancel_lottery(ctx: Context<CancelLottery>) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(
+        token_lottery.now(&clock) > token_lottery.cancel_deadline(),
+        ErrorCode::CancelTimeoutNotReached
+    );
+
+    token_lottery.cancelled = true;
+    msg!("Lottery {} cancelled", token_lottery.lottery_id);
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+use crate::error::ErrorCode;
+use crate::instructions::draw::draw_winners;
+use crate::instructions::payment::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    // シードをコミットできるのはチケットの購入者のみ
+    #[account(
+        seeds = [b"buyer".as_ref(), token_lottery.key().as_ref(), token_lottery.round.to_le_bytes().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_record: Box<Account<'info, BuyerRecord>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SeedCommitment::INIT_SPACE,
+        seeds = [b"seed".as_ref(), token_lottery.key().as_ref(), token_lottery.round.to_le_bytes().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub seed_commitment: Box<Account<'info, SeedCommitment>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealSeed<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        mut,
+        seeds = [b"seed".as_ref(), token_lottery.key().as_ref(), token_lottery.round.to_le_bytes().as_ref(), payer.key().as_ref()],
+        bump = seed_commitment.bump,
+    )]
+    pub seed_commitment: Box<Account<'info, SeedCommitment>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealWinnerFromSeeds<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    // トークン払いの抽選でkeeper_tipを受け取る場合のみ必要
+    pub payment: PaymentAccounts<'info>,
+}
+
+pub fn process_commit_seed(ctx: Context<CommitSeed>, seed_hash: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(
+        token_lottery.randomness_mode == RandomnessMode::CommitReveal,
+        ErrorCode::InvalidRandomnessMode
+    );
+    require!(token_lottery.now(&clock) <= token_lottery.lottery_end, ErrorCode::LotteryNotOpen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+
+    let seed_commitment = &mut ctx.accounts.seed_commitment;
+    seed_commitment.bump = ctx.bumps.seed_commitment;
+    seed_commitment.owner = ctx.accounts.payer.key();
+    seed_commitment.seed_hash = seed_hash;
+    seed_commitment.revealed = false;
+
+    token_lottery.seeds_committed += 1;
+
+    Ok(())
+}
+
+pub fn process_reveal_seed(ctx: Context<RevealSeed>, seed: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    let now = token_lottery.now(&clock);
+    require!(
+        now > token_lottery.lottery_end && now <= token_lottery.seed_reveal_deadline(),
+        ErrorCode::SeedRevealNotOpen
+    );
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+
+    let seed_commitment = &mut ctx.accounts.seed_commitment;
+    require!(!seed_commitment.revealed, ErrorCode::SeedAlreadyRevealed);
+    require!(
+        hashv(&[seed.as_ref()]).to_bytes() == seed_commitment.seed_hash,
+        ErrorCode::SeedHashMismatch
+    );
+
+    for (acc, byte) in token_lottery.seed_accumulator.iter_mut().zip(seed.iter()) {
+        *acc ^= byte;
+    }
+    token_lottery.seeds_revealed += 1;
+    seed_commitment.revealed = true;
+
+    Ok(())
+}
+
+pub fn process_reveal_winner_from_seeds(ctx: Context<RevealWinnerFromSeeds>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    let token_lottery = &ctx.accounts.token_lottery;
+    require!(
+        token_lottery.randomness_mode == RandomnessMode::CommitReveal,
+        ErrorCode::InvalidRandomnessMode
+    );
+    require!(
+        token_lottery.now(&clock) > token_lottery.seed_reveal_deadline(),
+        ErrorCode::LotteryNotCompleted
+    );
+    require!(token_lottery.seeds_revealed > 0, ErrorCode::RandomnessNotResolved);
+
+    // 抽選ごとに異なる値になるよう、lottery_idと合わせてハッシュする
+    let random_value = hashv(&[
+        token_lottery.seed_accumulator.as_ref(),
+        token_lottery.lottery_id.to_le_bytes().as_ref(),
+    ])
+    .to_bytes();
+
+    draw_winners(
+        &mut ctx.accounts.token_lottery,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payment,
+        random_value,
+    )
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitYieldSource<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + YieldSource::INIT_SPACE,
+        seeds = [b"yield_source".as_ref(), token_lottery.key().as_ref()],
+        bump,
+    )]
+    pub yield_source: Account<'info, YieldSource>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundYieldSource<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"yield_source".as_ref(), yield_source.token_lottery.as_ref()],
+        bump = yield_source.bump,
+    )]
+    pub yield_source: Account<'info, YieldSource>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestYield<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    #[account(
+        mut,
+        seeds = [b"yield_source".as_ref(), token_lottery.key().as_ref()],
+        bump = yield_source.bump,
+    )]
+    pub yield_source: Account<'info, YieldSource>,
+}
+
+pub fn process_init_yield_source(ctx: Context<InitYieldSource>, reward_rate_bps: u16) -> Result<()> {
+    require!(
+        ctx.accounts.payer.key() == ctx.accounts.token_lottery.authority,
+        ErrorCode::NotAuthorized
+    );
+    require!(
+        ctx.accounts.token_lottery.prize_mode == PrizeMode::NoLoss,
+        ErrorCode::InvalidPrizeMode
+    );
+    require!(reward_rate_bps <= BASIS_POINTS, ErrorCode::InvalidRewardRate);
+
+    let yield_source = &mut ctx.accounts.yield_source;
+    yield_source.bump = ctx.bumps.yield_source;
+    yield_source.token_lottery = ctx.accounts.token_lottery.key();
+    yield_source.reward_rate_bps = reward_rate_bps;
+    yield_source.rewards_available = 0;
+    Ok(())
+}
+
+pub fn process_fund_yield_source(ctx: Context<FundYieldSource>, amount: u64) -> Result<()> {
+    let transfer_accounts = Transfer {
+        from: ctx.accounts.payer.to_account_info(),
+        to: ctx.accounts.yield_source.to_account_info(),
+    };
+
+    transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_accounts),
+        amount,
+    )?;
+
+    let yield_source = &mut ctx.accounts.yield_source;
+    yield_source.rewards_available = yield_source.rewards_available
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+pub fn process_harvest_yield(ctx: Context<HarvestYield>) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(
+        token_lottery.prize_mode == PrizeMode::NoLoss,
+        ErrorCode::InvalidPrizeMode
+    );
+    require!(
+        token_lottery.now(&clock) >= token_lottery.lottery_end,
+        ErrorCode::LotteryNotCompleted
+    );
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(!token_lottery.yield_harvested, ErrorCode::YieldAlreadyHarvested);
+
+    // 利回りは現在のラウンドの預け入れ分のみ
+    // （前のラウンドから引き出されずに残っている預け入れは、このラウンドの抽選に参加していない）
+    let yield_source = &mut ctx.accounts.yield_source;
+    let earned = yield_source.earned(token_lottery.round_deposits)?;
+
+    yield_source.rewards_available = yield_source.rewards_available
+        .checked_sub(earned)
+        .ok_or(ErrorCode::MathOverflow)?;
+    yield_source.sub_lamports(earned)?;
+    token_lottery.add_lamports(earned)?;
+
+    // 手数料は預け入れではなく利回りから差し引く
+    // 預け入れは中止されても全額返金するので、利回りの手数料はすぐに引き出せる
+    let fee = (earned as u128)
+        .checked_mul(token_lottery.fee_bps as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / BASIS_POINTS as u128;
+    let fee = fee as u64;
+    token_lottery.fees_collected = token_lottery.fees_collected
+        .checked_add(fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+    token_lottery.lottery_pot_amount = token_lottery.lottery_pot_amount
+        .checked_add(earned.checked_sub(fee).ok_or(ErrorCode::MathOverflow)?)
+        .ok_or(ErrorCode::MathOverflow)?;
+    token_lottery.yield_harvested = true;
+
+    msg!("Yield harvested: {} (fee {})", earned, fee);
+
+    Ok(())
+}
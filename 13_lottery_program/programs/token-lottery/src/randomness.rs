@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+#[cfg(not(feature = "mock-randomness"))]
+use switchboard_on_demand::accounts::RandomnessAccountData;
+use crate::error::ErrorCode;
+#[cfg(not(feature = "mock-randomness"))]
+use crate::constants::SWITCHBOARD_PROGRAM_IDS;
+
+/// 乱数の提供元の抽象化
+/// commit_winner / reveal_winner は、このトレイトを通して乱数を取得する
+pub trait RandomnessSource {
+    /// 乱数がコミットされたスロット
+    fn seed_slot(&self) -> u64;
+    /// 解決済みの乱数（未解決の場合はRandomnessNotResolved）
+    fn get_value(&self, clock: &Clock) -> Result<[u8; 32]>;
+}
+
+#[cfg(not(feature = "mock-randomness"))]
+impl RandomnessSource for RandomnessAccountData {
+    fn seed_slot(&self) -> u64 {
+        self.seed_slot
+    }
+
+    fn get_value(&self, clock: &Clock) -> Result<[u8; 32]> {
+        RandomnessAccountData::get_value(self, clock.slot)
+            .map_err(|_| error!(ErrorCode::RandomnessNotResolved))
+    }
+}
+
+/// 乱数アカウントを読み込み、RandomnessSourceとしてfに渡す
+/// 所有者がSwitchboard On-Demandプログラムでない場合や、データが不正な場合はエラー
+#[cfg(not(feature = "mock-randomness"))]
+pub fn with_randomness<T>(
+    randomness_account: &AccountInfo,
+    f: impl FnOnce(&dyn RandomnessSource) -> Result<T>,
+) -> Result<T> {
+    require!(
+        SWITCHBOARD_PROGRAM_IDS.contains(randomness_account.owner),
+        ErrorCode::InvalidRandomnessOwner
+    );
+
+    let randomness_data = RandomnessAccountData::parse(randomness_account.data.borrow())
+        .map_err(|_| error!(ErrorCode::InvalidRandomnessAccount))?;
+
+    f(&*randomness_data)
+}
+
+/// ローカルテスト用の乱数アカウント
+/// Switchboardと同様に、コミット時のseed_slotと、解決後の乱数を持つ
+#[cfg(feature = "mock-randomness")]
+#[account]
+#[derive(InitSpace)]
+pub struct MockRandomness {
+    pub authority: Pubkey,
+    pub seed_slot: u64,
+    pub value: [u8; 32],
+    pub revealed: bool,
+}
+
+#[cfg(feature = "mock-randomness")]
+impl RandomnessSource for MockRandomness {
+    fn seed_slot(&self) -> u64 {
+        self.seed_slot
+    }
+
+    fn get_value(&self, _clock: &Clock) -> Result<[u8; 32]> {
+        require!(self.revealed, ErrorCode::RandomnessNotResolved);
+        Ok(self.value)
+    }
+}
+
+/// `mock-randomness` featureが有効な場合は、プログラムが所有するMockRandomnessを使用する
+#[cfg(feature = "mock-randomness")]
+pub fn with_randomness<T>(
+    randomness_account: &AccountInfo,
+    f: impl FnOnce(&dyn RandomnessSource) -> Result<T>,
+) -> Result<T> {
+    require!(
+        randomness_account.owner == &crate::ID,
+        ErrorCode::InvalidRandomnessOwner
+    );
+
+    let data = randomness_account.try_borrow_data()?;
+    let mock_randomness = MockRandomness::try_deserialize(&mut &data[..])
+        .map_err(|_| error!(ErrorCode::InvalidRandomnessAccount))?;
+
+    f(&mock_randomness)
+}
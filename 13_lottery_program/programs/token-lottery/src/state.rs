@@ -0,0 +1,398 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::ErrorCode;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TokenLottery {
+    pub bump: u8,
+    pub lottery_id: u64,
+    pub winner_chosen: bool,
+    pub window_mode: WindowMode,
+    pub lottery_start: u64,
+    pub lottery_end: u64,
+    pub lottery_pot_amount: u64,
+    pub total_tickets: u64,
+    pub price: u64,
+    pub randomness_account: Pubkey,
+    pub authority: Pubkey,
+    /// 賞ごとの配分（basis points、合計10000）
+    #[max_len(5)]
+    pub prize_tiers: Vec<u16>,
+    /// 賞ごとの当選チケットID（重複なし）
+    #[max_len(5)]
+    pub winning_ticket_ids: Vec<u64>,
+    /// 賞ごとの受け取り済みフラグ
+    #[max_len(5)]
+    pub tier_claimed: Vec<bool>,
+    /// 当選者決定時点の賞金総額
+    pub prize_pool: u64,
+    /// 支払いに使うトークンのミント（NoneはSOL払い）
+    pub payment_mint: Option<Pubkey>,
+    /// 購入時に差し引く手数料（basis points）
+    pub fee_bps: u16,
+    /// 手数料の受取人
+    pub fee_recipient: Pubkey,
+    /// 引き出せる手数料（当選者が決まったラウンドの手数料）
+    pub fees_collected: u64,
+    /// 中止された場合はtrue（チケットの返金のみ可能になる）
+    pub cancelled: bool,
+    /// 販売できるチケットの最大枚数
+    pub max_tickets: u64,
+    /// 1ウォレットが購入できるチケットの最大枚数（0は上限なし）
+    pub max_tickets_per_wallet: u64,
+    pub ticket_mode: TicketMode,
+    /// reveal_winnerの呼び出し元に賞金プールから支払う報酬
+    pub keeper_tip: u64,
+    pub randomness_mode: RandomnessMode,
+    /// CommitRevealモードで公開されたシードのXOR
+    pub seed_accumulator: [u8; 32],
+    pub seeds_committed: u64,
+    pub seeds_revealed: u64,
+    /// 現在のラウンド（0から始まる）
+    pub round: u64,
+    /// 現在のラウンドの最初のチケットID（チケットIDはラウンドをまたいで通し番号）
+    pub round_first_ticket_id: u64,
+    /// 当選者が決まったスロット（受け取り期限の起点）
+    pub draw_slot: u64,
+    /// チケットのメタデータJSONのベースURI（末尾の"/"なし）
+    #[max_len(160)]
+    pub base_uri: String,
+    #[max_len(10)]
+    pub symbol: String,
+    pub prize_mode: PrizeMode,
+    /// NoLossモードで引き出されていない預け入れの合計（賞金プールには含まない）
+    pub deposits: u64,
+    /// NoLossモードで、現在のラウンドの利回りを賞金プールに移したかどうか
+    pub yield_harvested: bool,
+    /// 現在のラウンドのチケット代金から差し引いた手数料
+    /// 中止された場合は返金し、当選者が決まるとfees_collectedに移す
+    pub round_fees: u64,
+    /// 現在のラウンドのチケット代金のうち賞金プールに積み立てた額（中止された場合に返金する分）
+    pub round_pot: u64,
+    /// NoLossモードで現在のラウンドに預け入れた額（利回りの計算に使う）
+    /// 前のラウンドから引き出されずに残っている預け入れは含まない
+    pub round_deposits: u64,
+}
+
+/// initialize_config / update_config の設定値
+/// update_configでは、支払い方法・手数料の受取人・各モード・メタデータは現在の値のまま渡す
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LotteryConfig {
+    pub window_mode: WindowMode,
+    pub start: u64,
+    pub end: u64,
+    pub price: u64,
+    /// 賞ごとの配分（basis points、合計10000）
+    pub prize_tiers: Vec<u16>,
+    /// 支払いに使うトークンのミント（NoneはSOL払い）
+    pub payment_mint: Option<Pubkey>,
+    pub fee_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub max_tickets: u64,
+    /// 0は上限なし
+    pub max_tickets_per_wallet: u64,
+    pub ticket_mode: TicketMode,
+    pub keeper_tip: u64,
+    pub randomness_mode: RandomnessMode,
+    pub base_uri: String,
+    pub symbol: String,
+    pub prize_mode: PrizeMode,
+}
+
+/// 終了したラウンドの結果
+#[account]
+#[derive(InitSpace)]
+pub struct RoundResult {
+    pub lottery_id: u64,
+    pub round: u64,
+    pub first_ticket_id: u64,
+    pub total_tickets: u64,
+    #[max_len(5)]
+    pub winning_ticket_ids: Vec<u64>,
+    #[max_len(5)]
+    pub tier_claimed: Vec<bool>,
+    pub prize_pool: u64,
+    /// 次のラウンドに繰り越した額
+    pub rolled_over: u64,
+}
+
+/// 当選者を決める乱数の取得方法（抽選ごとにinitialize_configで選ぶ）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RandomnessMode {
+    /// Switchboardのランダムネスアカウント（`mock-randomness` featureではMockRandomness）
+    Oracle,
+    /// 参加者がコミットしたシードを公開してまとめる（オラクル不要）
+    CommitReveal,
+}
+
+/// CommitRevealモードで参加者がコミットしたシードのハッシュ
+#[account]
+#[derive(InitSpace)]
+pub struct SeedCommitment {
+    pub bump: u8,
+    pub owner: Pubkey,
+    /// sha256(seed)
+    pub seed_hash: [u8; 32],
+    pub revealed: bool,
+}
+
+/// 賞金の原資（抽選ごとにinitialize_configで選ぶ）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PrizeMode {
+    /// チケット代金（手数料を除く）が賞金になる
+    Pot,
+    /// チケット代金は預け入れで、抽選後に全額引き出せる
+    /// 預け入れで得た利回り（YieldSource）だけが賞金になる
+    NoLoss,
+}
+
+/// NoLossモードの利回りの提供元
+/// ステーキング報酬の流入を模擬し、fund_yield_sourceで入金されたSOLを原資とする
+#[account]
+#[derive(InitSpace)]
+pub struct YieldSource {
+    pub bump: u8,
+    pub token_lottery: Pubkey,
+    /// 1ラウンドあたりの預け入れ額に対する利回り（basis points）
+    pub reward_rate_bps: u16,
+    /// まだ支払っていない報酬（このアカウントのlamportsのうち、レント以外）
+    pub rewards_available: u64,
+}
+
+impl YieldSource {
+    /// depositsに対する1ラウンド分の利回り（原資を超えない）
+    pub fn earned(&self, deposits: u64) -> Result<u64> {
+        let earned = (deposits as u128)
+            .checked_mul(self.reward_rate_bps as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            / BASIS_POINTS as u128;
+        Ok((earned as u64).min(self.rewards_available))
+    }
+}
+
+/// lottery_start / lottery_end の単位（抽選ごとにinitialize_configで選ぶ）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum WindowMode {
+    /// スロット番号
+    Slot,
+    /// unixタイムスタンプ（秒）
+    Timestamp,
+}
+
+/// チケットの表現方法（抽選ごとにinitialize_configで選ぶ）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TicketMode {
+    /// Metaplexのコレクションに属するNFT（メタデータ・マスターエディション付き）
+    Nft,
+    /// 所有者とチケットIDの範囲だけを記録するTicketアカウント（NFTなし）
+    Compact,
+}
+
+/// チケットNFT1つが表すチケットIDの範囲
+#[account]
+#[derive(InitSpace)]
+pub struct Ticket {
+    pub bump: u8,
+    /// 購入者（Compactモードではこのアカウントが所有権を表す）
+    pub owner: Pubkey,
+    pub first_ticket_id: u64,
+    pub quantity: u64,
+    /// チケットNFTのミント（Compactモードでは Pubkey::default()）
+    pub mint: Pubkey,
+    /// 受け取り済みの賞（ビットごとに1つの賞）
+    /// 1つのチケットで同じ賞を二度受け取れないようにする
+    pub claimed_tiers: u8,
+    /// 支払った代金（NoLossモードで引き出せる額）
+    pub deposit: u64,
+}
+
+impl Ticket {
+    pub fn contains(&self, ticket_id: u64) -> bool {
+        ticket_id >= self.first_ticket_id && ticket_id - self.first_ticket_id < self.quantity
+    }
+
+    pub fn is_claimed(&self, tier: u8) -> bool {
+        self.claimed_tiers & (1 << tier) != 0
+    }
+
+    pub fn mark_claimed(&mut self, tier: u8) {
+        self.claimed_tiers |= 1 << tier;
+    }
+}
+
+/// ウォレットごとの購入枚数
+#[account]
+#[derive(InitSpace)]
+pub struct BuyerRecord {
+    pub owner: Pubkey,
+    pub round: u64,
+    pub tickets_bought: u64,
+}
+
+impl TokenLottery {
+    /// コレクションNFTのメタデータURI
+    pub fn collection_uri(&self) -> String {
+        format!("{}/collection.json", self.base_uri)
+    }
+
+    /// チケットNFTのメタデータURI（まとめ買いの場合は先頭のチケットID）
+    pub fn ticket_uri(&self, first_ticket_id: u64) -> String {
+        format!("{}/{}.json", self.base_uri, first_ticket_id)
+    }
+
+    /// 当選したチケットNFTのメタデータURI
+    pub fn winner_uri(&self, first_ticket_id: u64) -> String {
+        format!("{}/winner/{}.json", self.base_uri, first_ticket_id)
+    }
+
+    /// window_modeに応じた現在時刻（スロットまたはunixタイムスタンプ）
+    pub fn now(&self, clock: &Clock) -> u64 {
+        match self.window_mode {
+            WindowMode::Slot => clock.slot,
+            WindowMode::Timestamp => clock.unix_timestamp.max(0) as u64,
+        }
+    }
+
+    /// これを過ぎても当選者が決まっていない場合、誰でも抽選を中止できる
+    pub fn cancel_deadline(&self) -> u64 {
+        let timeout = match self.window_mode {
+            WindowMode::Slot => CANCEL_TIMEOUT_SLOTS,
+            WindowMode::Timestamp => CANCEL_TIMEOUT_SECONDS,
+        };
+        self.lottery_end.saturating_add(timeout)
+    }
+
+    /// CommitRevealモードでシードを公開できる期限
+    pub fn seed_reveal_deadline(&self) -> u64 {
+        let window = match self.window_mode {
+            WindowMode::Slot => SEED_REVEAL_WINDOW_SLOTS,
+            WindowMode::Timestamp => SEED_REVEAL_WINDOW_SECONDS,
+        };
+        self.lottery_end.saturating_add(window)
+    }
+
+    /// 現在のラウンドで販売したチケット数
+    pub fn round_tickets(&self) -> u64 {
+        self.total_tickets - self.round_first_ticket_id
+    }
+
+    /// 賞金を受け取れるか確認し、賞金額を返す
+    pub fn check_claim(&self, tier: u8, ticket: &Ticket) -> Result<u64> {
+        require!(self.winner_chosen, ErrorCode::WinnerNotChosen);
+
+        // 賞ごとに独立して受け取れる
+        let tier_index = tier as usize;
+        require!(tier_index < self.winning_ticket_ids.len(), ErrorCode::InvalidTier);
+        require!(!self.tier_claimed[tier_index], ErrorCode::PrizeAlreadyClaimed);
+        require!(
+            ticket.contains(self.winning_ticket_id(tier)),
+            ErrorCode::IncorrectTicket
+        );
+        require!(!ticket.is_claimed(tier), ErrorCode::PrizeAlreadyClaimed);
+
+        self.tier_prize(tier)
+    }
+
+    pub fn record_claim(&mut self, tier: u8, prize: u64) {
+        self.lottery_pot_amount -= prize;
+        self.tier_claimed[tier as usize] = true;
+    }
+
+    /// quantity枚分の返金額と、そのうち購入時に手数料として差し引いた額
+    pub fn refund_amount(&self, quantity: u64) -> Result<(u64, u64)> {
+        let price = self.price
+            .checked_mul(quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let fee = self.ticket_fee()? * quantity;
+        Ok((price, fee))
+    }
+
+    /// 返金（NoLossモードでは預け入れの引き出し）できるか確認し、返金額と、そのうち手数料として差し引いた額を返す
+    pub fn check_refund(&self, ticket: &Ticket) -> Result<(u64, u64)> {
+        let current_round = ticket.first_ticket_id >= self.round_first_ticket_id;
+        match self.prize_mode {
+            PrizeMode::Pot => {
+                require!(self.cancelled, ErrorCode::LotteryNotCancelled);
+                require!(current_round, ErrorCode::TicketFromPreviousRound);
+                self.refund_amount(ticket.quantity)
+            }
+            PrizeMode::NoLoss => {
+                // 抽選が終わったラウンド（または中止された抽選）の預け入れはいつでも引き出せる
+                require!(
+                    !current_round || self.winner_chosen || self.cancelled,
+                    ErrorCode::DepositLocked
+                );
+                // 引き出すとチケット（NFTまたはTicketアカウント）がなくなるので、
+                // 当選したチケットは賞金を受け取るまで引き出せない
+                if current_round {
+                    let unclaimed_win = self.winning_ticket_ids
+                        .iter()
+                        .zip(self.tier_claimed.iter())
+                        .any(|(&ticket_id, &claimed)| !claimed && ticket.contains(ticket_id));
+                    require!(!unclaimed_win, ErrorCode::PrizeNotClaimed);
+                }
+                Ok((ticket.deposit, 0))
+            }
+        }
+    }
+
+    pub fn record_refund(&mut self, ticket: &Ticket, price: u64, fee: u64) -> Result<()> {
+        match self.prize_mode {
+            PrizeMode::Pot => {
+                self.lottery_pot_amount -= price - fee;
+                self.round_pot -= price - fee;
+                self.round_fees -= fee;
+            }
+            PrizeMode::NoLoss => {
+                self.deposits = self.deposits
+                    .checked_sub(price)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                // 現在のラウンドの預け入れを引き出した場合は、利回りの計算からも除く
+                if ticket.first_ticket_id >= self.round_first_ticket_id {
+                    self.round_deposits = self.round_deposits
+                        .checked_sub(price)
+                        .ok_or(ErrorCode::MathOverflow)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// チケット1枚あたりの手数料
+    pub fn ticket_fee(&self) -> Result<u64> {
+        let fee = (self.price as u128)
+            .checked_mul(self.fee_bps as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            / BASIS_POINTS as u128;
+        Ok(fee as u64)
+    }
+
+    /// 指定した賞の当選チケットID（範囲外の場合は0）
+    pub fn winning_ticket_id(&self, tier: u8) -> u64 {
+        self.winning_ticket_ids.get(tier as usize).copied().unwrap_or_default()
+    }
+
+    /// 指定した賞の賞金額
+    /// 当選者がいない賞の配分と端数は1等に加算される
+    pub fn tier_prize(&self, tier: u8) -> Result<u64> {
+        let share = |bps: u16| -> Result<u64> {
+            let amount = (self.prize_pool as u128)
+                .checked_mul(bps as u128)
+                .ok_or(ErrorCode::MathOverflow)?
+                / BASIS_POINTS as u128;
+            Ok(amount as u64)
+        };
+
+        if tier > 0 {
+            return share(self.prize_tiers[tier as usize]);
+        }
+
+        let mut lower_tiers = 0u64;
+        for &bps in self.prize_tiers.iter().take(self.winning_ticket_ids.len()).skip(1) {
+            lower_tiers += share(bps)?;
+        }
+
+        Ok(self.prize_pool - lower_tiers)
+    }
+}
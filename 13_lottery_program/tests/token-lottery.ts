@@ -1,10 +1,19 @@
 import * as anchor from "@coral-xyz/anchor";
-import * as sb from "@switchboard-xyz/on-demand";
 import { Program } from "@coral-xyz/anchor";
 import { TokenLottery } from "../target/types/token_lottery";
 import { TOKEN_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { createHash } from "crypto";
 
+// Switchboardの代わりにプログラム所有のMockRandomnessで抽選するため、
+// mock-randomness featureでビルドしたプログラムに対して実行する
+//   anchor test -- --features mock-randomness
 describe("token-lottery", () => {
   // Configure the client to use the local cluster.
   const provider = anchor.AnchorProvider.env();
@@ -13,10 +22,24 @@ describe("token-lottery", () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.TokenLottery as Program<TokenLottery>;
-  let switchboardProgram;
-  const rngKp = anchor.web3.Keypair.generate();
   let lotteryEndSlot: number;
 
+  // 1つのプログラムで複数の抽選を同時に運用できるよう、lottery_idで区別する
+  const lotteryId = new anchor.BN(Date.now());
+  const lotteryIdBuffer = lotteryId.toArrayLike(Buffer, "le", 8);
+  // 1等50%、2等30%、3等20%（basis points）
+  const PRIZE_TIERS = [5000, 3000, 2000];
+  // チケット代金の5%を手数料としてfeeRecipientに送る
+  const FEE_BPS = 500;
+  const TICKET_PRICE = 10000;
+  const MAX_TICKETS = 100;
+  const MAX_TICKETS_PER_WALLET = 10;
+  // reveal_winnerを呼び出したキーパーへの報酬（lamports）
+  const KEEPER_TIP = 1000;
+  // チケットのメタデータは {BASE_URI}/{ticket_id}.json、当選後は {BASE_URI}/winner/{ticket_id}.json
+  const BASE_URI = "https://example.com/token-lottery";
+  const SYMBOL = "TICKET";
+
   // Helper function to wait for a specific slot
   async function waitForSlot(targetSlot: number) {
     let currentSlot = await connection.getSlot();
@@ -28,23 +51,163 @@ describe("token-lottery", () => {
     console.log(`Reached slot ${currentSlot}, target was ${targetSlot}`);
   }
 
+  type LotteryConfig = anchor.IdlTypes<TokenLottery>["lotteryConfig"];
+
+  // initialize_config / update_config に渡す設定（overridesで一部の値を変更する）
+  function lotteryConfig(
+    end: number,
+    overrides: Partial<LotteryConfig> = {}
+  ): LotteryConfig {
+    return {
+      windowMode: { slot: {} },
+      start: new anchor.BN(0),
+      end: new anchor.BN(end),
+      price: new anchor.BN(TICKET_PRICE),
+      prizeTiers: PRIZE_TIERS,
+      paymentMint: null, // nullの場合はSOL払い
+      feeBps: FEE_BPS,
+      feeRecipient: wallet.publicKey,
+      maxTickets: new anchor.BN(MAX_TICKETS),
+      maxTicketsPerWallet: new anchor.BN(MAX_TICKETS_PER_WALLET),
+      ticketMode: { nft: {} },
+      keeperTip: new anchor.BN(KEEPER_TIP),
+      randomnessMode: { oracle: {} },
+      baseUri: BASE_URI,
+      symbol: SYMBOL,
+      prizeMode: { pot: {} },
+      ...overrides,
+    };
+  }
+
   const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey(
     "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
   );
 
-  before("Loading switchboard program", async () => {
-    const switchboardIDL = await anchor.Program.fetchIdl(sb.ON_DEMAND_DEVNET_PID, {
-      connection: new anchor.web3.Connection(
-        "https://api.devnet.solana.com"
-      ),
+  // MockRandomnessで当選者を決める
+  // Switchboardと同様に、乱数アカウントの作成とcommit_winnerを同じトランザクションで行い、
+  // 乱数を解決してからreveal_winnerを呼び出す
+  async function drawWithMockRandomness(
+    lotteryAddress: anchor.web3.PublicKey,
+    value: number[]
+  ) {
+    const rngKp = await commitMockRandomness(lotteryAddress);
+    await revealMockRandomness(lotteryAddress, rngKp, value);
+  }
+
+  // keeperを省略した場合はproviderのウォレットがコミットする
+  async function commitMockRandomness(
+    lotteryAddress: anchor.web3.PublicKey,
+    keeper: anchor.web3.Keypair = wallet.payer
+  ) {
+    const rngKp = anchor.web3.Keypair.generate();
+
+    const initRandomnessIx = await program.methods
+      .initMockRandomness()
+      .accounts({
+        payer: keeper.publicKey,
+        mockRandomness: rngKp.publicKey,
+      })
+      .instruction();
+
+    const commitSig = await program.methods
+      .commitWinner()
+      .accounts({
+        payer: keeper.publicKey,
+        tokenLottery: lotteryAddress,
+        randomnessAccountData: rngKp.publicKey,
+      })
+      .preInstructions([initRandomnessIx])
+      .signers([keeper, rngKp])
+      .rpc();
+    console.log("Transaction Signature for commit: ", commitSig);
+    return rngKp;
+  }
+
+  async function revealMockRandomness(
+    lotteryAddress: anchor.web3.PublicKey,
+    rngKp: anchor.web3.Keypair,
+    value: number[],
+    keeper: anchor.web3.Keypair = wallet.payer
+  ) {
+    const randomness = await program.account.mockRandomness.fetch(
+      rngKp.publicKey
+    );
+    const resolveIx = await program.methods
+      .setMockRandomness(randomness.seedSlot, value)
+      .accounts({
+        payer: keeper.publicKey,
+        mockRandomness: rngKp.publicKey,
+      })
+      .instruction();
+
+    const revealSig = await program.methods
+      .revealWinner()
+      .accounts({
+        tokenLottery: lotteryAddress,
+        randomnessAccountData: rngKp.publicKey,
+      })
+      .preInstructions([resolveIx])
+      .signers([keeper])
+      .rpc();
+    console.log("Transaction Signature for reveal: ", revealSig);
+  }
+
+  // プログラムのselect_winnersと同じ計算（ラウンド内のインデックスを返す）
+  // 乱数の先頭128ビットをrとし、floor(r * n / 2^128)で選び、r * n の下位128ビットを次の乱数にする
+  function selectWinners(
+    randomValue: number[],
+    totalTickets: number,
+    numWinners: number
+  ): number[] {
+    const mask = new anchor.BN(1).shln(128).subn(1);
+    let r = new anchor.BN(randomValue.slice(0, 16), "le");
+    const selected: number[] = [];
+    const winners: number[] = [];
+    for (let k = 0; k < Math.min(numWinners, totalTickets); k++) {
+      const product = r.muln(totalTickets - k);
+      let ticketId = product.shrn(128).toNumber();
+      r = product.and(mask);
+
+      // 未選択のチケットの中で index 番目のIDに変換する
+      for (const id of selected) {
+        if (id <= ticketId) {
+          ticketId += 1;
+        }
+      }
+      selected.push(ticketId);
+      selected.sort((a, b) => a - b);
+      winners.push(ticketId);
+    }
+    return winners;
+  }
+
+  async function airdrop(to: anchor.web3.PublicKey) {
+    const sig = await connection.requestAirdrop(
+      to,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    const blockhashContext = await connection.getLatestBlockhash();
+    await connection.confirmTransaction({
+      signature: sig,
+      blockhash: blockhashContext.blockhash,
+      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     });
-    switchboardProgram = new anchor.Program(switchboardIDL, provider);
-  });
+  }
+
+  const tokenLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("token_lottery"), lotteryIdBuffer],
+    program.programId
+  )[0];
+
+  // 購入したチケットNFTごとの先頭ID（当選チケットIDからNFTを探すのに使う）
+  const firstTicketIds: number[] = [];
+  let ticketsSold = 0;
 
-  async function buyTicket() {
+  async function buyTicket(quantity: number) {
     const buyTicketIx = await program.methods
-      .buyTicket()
+      .buyTicket(new anchor.BN(quantity))
       .accounts({
+        tokenLottery: tokenLotteryAddress,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .instruction();
@@ -72,6 +235,9 @@ describe("token-lottery", () => {
       wallet.payer,
     ]);
     console.log("buy ticket ", sig);
+
+    firstTicketIds.push(ticketsSold);
+    ticketsSold += quantity;
   }
 
   it("Is initialized!", async () => {
@@ -83,7 +249,7 @@ describe("token-lottery", () => {
     console.log("Lottery end slot", lotteryEndSlot);
 
     const mint = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("collection_mint")],
+      [Buffer.from("collection_mint"), lotteryIdBuffer],
       program.programId
     )[0];
 
@@ -107,15 +273,11 @@ describe("token-lottery", () => {
     )[0];
 
     const initConfigIx = await program.methods
-      .initializeConfig(
-        new anchor.BN(0),
-        new anchor.BN(lotteryEndSlot),
-        new anchor.BN(10000)
-      )
+      .initializeConfig(lotteryId, lotteryConfig(lotteryEndSlot))
       .instruction();
 
     const initLotteryIx = await program.methods
-      .initializeLottery()
+      .initializeLottery(lotteryId)
       .accounts({
         masterEdition: masterEdition,
         metadata: metadata,
@@ -140,11 +302,55 @@ describe("token-lottery", () => {
   });
 
   it("Is buying tickets!", async () => {
-    await buyTicket();
-    await buyTicket();
-    await buyTicket();
-    await buyTicket();
-    await buyTicket();
+    // 購入ごとにTicketPurchasedイベントが発行される
+    const purchases = [];
+    const listener = program.addEventListener("ticketPurchased", (event) => {
+      purchases.push(event);
+    });
+
+    // 1枚ずつ2回と、3枚まとめて1回の計5枚
+    await buyTicket(1);
+    await buyTicket(1);
+    await buyTicket(3);
+
+    await program.removeEventListener(listener);
+
+    // まとめ買いは先頭のチケットIDと枚数で1イベント
+    const expected = [
+      { ticketId: 0, quantity: 1 },
+      { ticketId: 1, quantity: 1 },
+      { ticketId: 2, quantity: 3 },
+    ];
+    if (
+      purchases.length !== expected.length ||
+      purchases.some(
+        (event, i) =>
+          !event.lotteryId.eq(lotteryId) ||
+          !event.round.isZero() ||
+          !event.buyer.equals(wallet.publicKey) ||
+          event.ticketId.toNumber() !== expected[i].ticketId ||
+          event.quantity.toNumber() !== expected[i].quantity ||
+          event.price.toNumber() !== TICKET_PRICE * expected[i].quantity
+      )
+    ) {
+      throw new Error("TicketPurchased events do not match the purchases");
+    }
+
+    // 当選者が決まるまで、このラウンドの手数料は引き出せない（中止された場合は返金する）
+    try {
+      await program.methods
+        .withdrawFees()
+        .accounts({
+          tokenLottery: tokenLotteryAddress,
+          feeRecipient: wallet.publicKey,
+        })
+        .rpc();
+      throw new Error("fees were withdrawn before the draw");
+    } catch (err) {
+      if (!err.toString().includes("NoFeesToWithdraw")) {
+        throw err;
+      }
+    }
   });
 
   it("Is committing and revealing a winner", async () => {
@@ -152,154 +358,1241 @@ describe("token-lottery", () => {
     console.log("Waiting for lottery to end...");
     await waitForSlot(lotteryEndSlot + 1);
 
-    // Use Devnet queue
-    const queue = sb.ON_DEMAND_DEVNET_QUEUE;
+    const reveals = [];
+    const listener = program.addEventListener("winnerRevealed", (event) => {
+      reveals.push(event);
+    });
+    await drawWithMockRandomness(tokenLotteryAddress, Array(32).fill(7));
+    await program.removeEventListener(listener);
 
-    const queueAccount = new sb.Queue(switchboardProgram, queue);
-    console.log("Queue account", queue.toString());
-    try {
-      await queueAccount.loadData();
-    } catch (err) {
-      console.log("Queue account not found");
-      process.exit(1);
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    if (
+      !lotteryConfig.winnerChosen ||
+      lotteryConfig.winningTicketIds.length !== PRIZE_TIERS.length
+    ) {
+      throw new Error("winners were not chosen");
     }
 
-    const [randomness, ix] = await sb.Randomness.create(
-      switchboardProgram,
-      rngKp,
-      queue
-    );
-    console.log("Created randomness account..");
-    console.log("Randomness account", randomness.pubkey.toBase58());
-    console.log("rkp account", rngKp.publicKey.toBase58());
-    const createRandomnessTx = await sb.asV0Tx({
-      connection: connection,
-      ixs: [ix],
-      payer: wallet.publicKey,
-      signers: [wallet.payer, rngKp],
-      computeUnitPrice: 75_000,
-      computeUnitLimitMultiple: 1.3,
-    });
+    // イベントの値だけから当選チケットIDを再計算できる
+    const revealed = reveals[0];
+    const recomputed = selectWinners(
+      revealed.randomValue,
+      revealed.totalTickets.toNumber(),
+      PRIZE_TIERS.length
+    ).map((index) => revealed.roundFirstTicketId.toNumber() + index);
+    if (
+      reveals.length !== 1 ||
+      !revealed.lotteryId.eq(lotteryId) ||
+      revealed.totalTickets.toNumber() !== 5 ||
+      !revealed.prizePool.eq(lotteryConfig.prizePool) ||
+      revealed.winningTicketIds.some(
+        (id, i) =>
+          !id.eq(lotteryConfig.winningTicketIds[i]) ||
+          id.toNumber() !== recomputed[i]
+      )
+    ) {
+      throw new Error("WinnerRevealed event does not match the draw");
+    }
+  });
 
-    const blockhashContext = await connection.getLatestBlockhashAndContext();
+  it("Cannot cancel a lottery after the winners are chosen", async () => {
+    try {
+      await program.methods
+        .cancelLottery()
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      throw new Error("cancelled a lottery whose winners were already chosen");
+    } catch (error) {
+      if (!error.message.includes("WinnerChosen")) {
+        throw error;
+      }
+      console.log("Cancellation was correctly rejected");
+    }
+  });
 
-    const createRandomnessSignature = await connection.sendTransaction(
-      createRandomnessTx
+  it("Is claiming a prize", async () => {
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
     );
-    await connection.confirmTransaction({
-      signature: createRandomnessSignature,
-      blockhash: blockhashContext.value.blockhash,
-      lastValidBlockHeight: blockhashContext.value.lastValidBlockHeight,
-    });
     console.log(
-      "Transaction Signature for randomness account creation: ",
-      createRandomnessSignature
+      "Lottery winners",
+      lotteryConfig.winningTicketIds.map((id) => id.toString())
     );
+    console.log("Lottery config", lotteryConfig);
 
-    const sbCommitIx = await randomness.commitIx(queue);
+    const tokenAccounts = await connection.getParsedTokenAccountsByOwner(
+      wallet.publicKey,
+      { programId: TOKEN_PROGRAM_ID }
+    );
+    tokenAccounts.value.forEach(async (account) => {
+      console.log("Token account mint", account.account.data.parsed.info.mint);
+      console.log("Token account address", account.pubkey.toBase58());
+    });
 
-    const commitIx = await program.methods
-      .commitWinner()
+    // 賞ごとに当選チケットで賞金を受け取る（テストでは全チケットを同じウォレットが保有）
+    for (let tier = 0; tier < lotteryConfig.winningTicketIds.length; tier++) {
+      const winningTicketId = lotteryConfig.winningTicketIds[tier].toNumber();
+      // 当選チケットIDを含むNFTの先頭ID
+      const firstTicketId = Math.max(
+        ...firstTicketIds.filter((id) => id <= winningTicketId)
+      );
+      const firstTicketIdBn = new anchor.BN(firstTicketId);
+      const winningMint = anchor.web3.PublicKey.findProgramAddressSync(
+        [lotteryIdBuffer, firstTicketIdBn.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+      console.log("Winning mint", winningMint.toBase58());
+
+      const winningTokenAddress = getAssociatedTokenAddressSync(
+        winningMint,
+        wallet.publicKey
+      );
+      console.log("Winning token address", winningTokenAddress.toBase58());
+
+      // 当選チケットのメタデータを当選者用のURIに変更する
+      const revealTicketIx = await program.methods
+        .revealWinningTicket(tier, firstTicketIdBn)
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .instruction();
+
+      const claimIx = await program.methods
+        .claimPrize(tier, firstTicketIdBn)
+        .accounts({
+          tokenLottery: tokenLotteryAddress,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+
+      const blockhashContext = await connection.getLatestBlockhash();
+
+      const claimTx = new anchor.web3.Transaction({
+        blockhash: blockhashContext.blockhash,
+        lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
+        feePayer: wallet.payer.publicKey,
+      })
+        .add(revealTicketIx)
+        .add(claimIx);
+
+      const claimSig = await anchor.web3.sendAndConfirmTransaction(
+        connection,
+        claimTx,
+        [wallet.payer]
+      );
+      console.log(claimSig);
+    }
+  });
+
+  it("Is withdrawing fees", async () => {
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const expectedFees = Math.floor((TICKET_PRICE * FEE_BPS) / 10000) * 5;
+    console.log("Fees collected", lotteryConfig.feesCollected.toString());
+    if (lotteryConfig.feesCollected.toNumber() !== expectedFees) {
+      throw new Error(
+        `expected ${expectedFees} fees, got ${lotteryConfig.feesCollected}`
+      );
+    }
+
+    await program.methods
+      .withdrawFees()
       .accounts({
-        randomnessAccountData: randomness.pubkey,
+        tokenLottery: tokenLotteryAddress,
+        feeRecipient: wallet.publicKey,
       })
-      .instruction();
+      .rpc();
+
+    const updatedConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    if (!updatedConfig.feesCollected.isZero()) {
+      throw new Error("fees were not withdrawn");
+    }
+  });
+
+  it("Is starting the next round", async () => {
+    // 全ての賞が受け取られたので、受け取り期限を待たずに次のラウンドを開始できる
+    const slot = await connection.getSlot();
+    const before = await program.account.tokenLottery.fetch(tokenLotteryAddress);
+
+    await program.methods
+      .startNextRound(new anchor.BN(slot), new anchor.BN(slot + 1000))
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc();
+
+    const roundResultAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("round_result"),
+        tokenLotteryAddress.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const roundResult = await program.account.roundResult.fetch(
+      roundResultAddress
+    );
+
+    // 受け取られずに残った賞金プールがそのまま次のラウンドに繰り越される
+    const after = await program.account.tokenLottery.fetch(tokenLotteryAddress);
+    if (
+      !roundResult.rolledOver.eq(before.lotteryPotAmount) ||
+      !after.lotteryPotAmount.eq(roundResult.rolledOver) ||
+      roundResult.winningTicketIds.some(
+        (id, i) => !id.eq(before.winningTicketIds[i])
+      )
+    ) {
+      throw new Error("round result does not match the finished round");
+    }
+    if (
+      after.round.toNumber() !== 1 ||
+      !after.roundFirstTicketId.eq(before.totalTickets) ||
+      after.winnerChosen
+    ) {
+      throw new Error("next round was not started");
+    }
+  });
 
-    const commitTx = await sb.asV0Tx({
-      connection: switchboardProgram.provider.connection,
-      ixs: [sbCommitIx, commitIx],
-      payer: wallet.publicKey,
-      signers: [wallet.payer],
-      computeUnitPrice: 75_000,
-      computeUnitLimitMultiple: 1.3,
+  it("Is updating the config before the first sale", async () => {
+    // 新しいラウンドではまだチケットが売れていないので設定を変更できる
+    const slot = await connection.getSlot();
+
+    await program.methods
+      .updateConfig(
+        lotteryConfig(slot + 2000, {
+          start: new anchor.BN(slot),
+          price: new anchor.BN(TICKET_PRICE * 2),
+        })
+      )
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc();
+
+    const config = await program.account.tokenLottery.fetch(tokenLotteryAddress);
+    if (config.price.toNumber() !== TICKET_PRICE * 2) {
+      throw new Error("config was not updated");
+    }
+
+    // 開始が終了より後の設定は拒否される
+    try {
+      await program.methods
+        .updateConfig(
+          lotteryConfig(slot, {
+            windowMode: { timestamp: {} },
+            start: new anchor.BN(slot + 2000),
+          })
+        )
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      throw new Error("invalid window was accepted");
+    } catch (err) {
+      if (!err.toString().includes("InvalidLotteryWindow")) {
+        throw err;
+      }
+    }
+
+    // チケットのモードなど、変更できない設定は拒否される
+    try {
+      await program.methods
+        .updateConfig(
+          lotteryConfig(slot + 2000, {
+            start: new anchor.BN(slot),
+            ticketMode: { compact: {} },
+          })
+        )
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      throw new Error("ticket mode was changed");
+    } catch (err) {
+      if (!err.toString().includes("ImmutableConfig")) {
+        throw err;
+      }
+    }
+
+    // キーパーへの報酬はチケット1枚の価格の10%まで
+    try {
+      await program.methods
+        .updateConfig(
+          lotteryConfig(slot + 2000, {
+            start: new anchor.BN(slot),
+            keeperTip: new anchor.BN(TICKET_PRICE),
+          })
+        )
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      throw new Error("a keeper tip above the cap was accepted");
+    } catch (err) {
+      if (!err.toString().includes("InvalidKeeperTip")) {
+        throw err;
+      }
+    }
+  });
+
+  describe("compact ticket mode", () => {
+    // NFTを発行しない軽量なチケット（Ticketアカウントのみ）
+    const compactLotteryId = new anchor.BN(Date.now() + 1);
+    const compactLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        compactLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const compactTicketAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("ticket"),
+        compactLotteryAddress.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const COMPACT_TICKETS = 4;
+    let compactEndSlot: number;
+
+    it("Is buying compact tickets", async () => {
+      const slot = await connection.getSlot();
+      compactEndSlot = slot + 30;
+
+      await program.methods
+        .initializeConfig(
+          compactLotteryId,
+          lotteryConfig(compactEndSlot, {
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+          })
+        )
+        .rpc();
+
+      await program.methods
+        .buyCompactTicket(new anchor.BN(COMPACT_TICKETS))
+        .accounts({ tokenLottery: compactLotteryAddress })
+        .rpc();
+
+      const ticket = await program.account.ticket.fetch(compactTicketAddress);
+      if (
+        !ticket.owner.equals(wallet.publicKey) ||
+        ticket.quantity.toNumber() !== COMPACT_TICKETS
+      ) {
+        throw new Error("compact ticket was not recorded");
+      }
+
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        compactLotteryAddress
+      );
+      console.log("Compact tickets sold", lotteryConfig.totalTickets.toString());
     });
 
-    const commitSignature = await connection.sendTransaction(commitTx);
-    await connection.confirmTransaction({
-      signature: commitSignature,
-      blockhash: blockhashContext.value.blockhash,
-      lastValidBlockHeight: blockhashContext.value.lastValidBlockHeight,
+    it("Is committing randomness only once", async () => {
+      await waitForSlot(compactEndSlot + 1);
+
+      // lottery_end後は誰でもコミットできる
+      const keeper = anchor.web3.Keypair.generate();
+      await airdrop(keeper.publicKey);
+      const rngKp = await commitMockRandomness(compactLotteryAddress, keeper);
+
+      // 結果を見てからやり直せないよう、別のランダムネスではコミットし直せない
+      try {
+        await commitMockRandomness(compactLotteryAddress);
+        throw new Error("the randomness was committed twice");
+      } catch (err) {
+        if (!err.toString().includes("RandomnessAlreadyCommitted")) {
+          throw err;
+        }
+      }
+
+      await revealMockRandomness(
+        compactLotteryAddress,
+        rngKp,
+        Array(32).fill(3),
+        keeper
+      );
     });
-    console.log("Transaction Signature for commit: ", commitSignature);
 
-    const sbRevealIx = await randomness.revealIx();
-    const revealIx = await program.methods
-      .revealWinner()
-      .accounts({
-        randomnessAccountData: randomness.pubkey,
-      })
-      .instruction();
+    it("Is claiming compact prizes", async () => {
+      // 全てのチケットが1つのTicketアカウントに含まれるので、全ての賞を受け取れる
+      for (let tier = 0; tier < PRIZE_TIERS.length; tier++) {
+        await program.methods
+          .claimCompactPrize(tier, new anchor.BN(0))
+          .accounts({ tokenLottery: compactLotteryAddress })
+          .rpc();
+      }
+
+      const ticket = await program.account.ticket.fetch(compactTicketAddress);
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        compactLotteryAddress
+      );
+      if (
+        ticket.claimedTiers !== (1 << PRIZE_TIERS.length) - 1 ||
+        !lotteryConfig.tierClaimed.every((claimed) => claimed)
+      ) {
+        throw new Error("compact prizes were not recorded as claimed");
+      }
 
-    const revealTx = await sb.asV0Tx({
-      connection: switchboardProgram.provider.connection,
-      ixs: [sbRevealIx, revealIx],
-      payer: wallet.publicKey,
-      signers: [wallet.payer],
-      computeUnitPrice: 75_000,
-      computeUnitLimitMultiple: 1.3,
+      try {
+        await program.methods
+          .claimCompactPrize(0, new anchor.BN(0))
+          .accounts({ tokenLottery: compactLotteryAddress })
+          .rpc();
+        throw new Error("a compact prize was claimed twice");
+      } catch (err) {
+        if (!err.toString().includes("PrizeAlreadyClaimed")) {
+          throw err;
+        }
+      }
     });
 
-    const revealSignature = await connection.sendTransaction(revealTx);
-    await connection.confirmTransaction({
-      signature: revealSignature,
-      blockhash: blockhashContext.value.blockhash,
-      lastValidBlockHeight: blockhashContext.value.lastValidBlockHeight,
+    it("Cannot claim a compact prize with someone else's ticket", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+      await airdrop(stranger.publicKey);
+
+      try {
+        await program.methods
+          .claimCompactPrize(0, new anchor.BN(0))
+          .accounts({
+            payer: stranger.publicKey,
+            tokenLottery: compactLotteryAddress,
+          })
+          .signers([stranger])
+          .rpc();
+        throw new Error("a prize was claimed with someone else's ticket");
+      } catch (err) {
+        if (!err.toString().includes("IncorrectTicket")) {
+          throw err;
+        }
+      }
+    });
+
+    it("Cannot refund a compact ticket unless the lottery is cancelled", async () => {
+      try {
+        await program.methods
+          .refundCompactTicket(new anchor.BN(0))
+          .accounts({ tokenLottery: compactLotteryAddress })
+          .rpc();
+        throw new Error("a ticket was refunded without cancellation");
+      } catch (err) {
+        if (!err.toString().includes("LotteryNotCancelled")) {
+          throw err;
+        }
+      }
     });
-    console.log("  Transaction Signature revealTx", revealSignature);
   });
 
-  it("Is claiming a prize", async () => {
-    const tokenLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("token_lottery")],
+  describe("token payments", () => {
+    // SPLトークンで支払う抽選では、代金はpotに入り、賞金と手数料はpotから支払われる
+    const tokenLotteryId = new anchor.BN(Date.now() + 7);
+    const splLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        tokenLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
       program.programId
     )[0];
-    const lotteryConfig = await program.account.tokenLottery.fetch(
-      tokenLotteryAddress
-    );
-    console.log("Lottery winner", lotteryConfig.winner);
-    console.log("Lottery config", lotteryConfig);
+    const potAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pot"), tokenLotteryId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+    const SPL_TICKETS = 2;
+    const INITIAL_BALANCE = TICKET_PRICE * 10;
+    const fee = Math.floor((TICKET_PRICE * FEE_BPS) / 10000) * SPL_TICKETS;
+    let paymentMint: anchor.web3.PublicKey;
+    let buyerTokenAccount: anchor.web3.PublicKey;
+    let splEndSlot: number;
 
-    const tokenAccounts = await connection.getParsedTokenAccountsByOwner(
-      wallet.publicKey,
-      { programId: TOKEN_PROGRAM_ID }
-    );
-    tokenAccounts.value.forEach(async (account) => {
-      console.log("Token account mint", account.account.data.parsed.info.mint);
-      console.log("Token account address", account.pubkey.toBase58());
+    async function tokenBalance(address: anchor.web3.PublicKey) {
+      return Number((await getAccount(connection, address)).amount);
+    }
+
+    function paymentAccounts(paymentAccount: anchor.web3.PublicKey) {
+      return {
+        paymentMint,
+        paymentAccount,
+        pot: potAddress,
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+      };
+    }
+
+    it("Is buying tickets with an SPL token", async () => {
+      paymentMint = await createMint(
+        connection,
+        wallet.payer,
+        wallet.publicKey,
+        null,
+        6
+      );
+      buyerTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          connection,
+          wallet.payer,
+          paymentMint,
+          wallet.publicKey
+        )
+      ).address;
+      await mintTo(
+        connection,
+        wallet.payer,
+        paymentMint,
+        buyerTokenAccount,
+        wallet.payer,
+        INITIAL_BALANCE
+      );
+
+      const slot = await connection.getSlot();
+      splEndSlot = slot + 20;
+      await program.methods
+        .initializeConfig(
+          tokenLotteryId,
+          lotteryConfig(splEndSlot, {
+            paymentMint,
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+          })
+        )
+        .rpc();
+      await program.methods
+        .initializePot(tokenLotteryId)
+        .accounts({ paymentMint, paymentTokenProgram: TOKEN_PROGRAM_ID })
+        .rpc();
+
+      await program.methods
+        .buyCompactTicket(new anchor.BN(SPL_TICKETS))
+        .accounts({
+          tokenLottery: splLotteryAddress,
+          payment: paymentAccounts(buyerTokenAccount),
+        })
+        .rpc();
+
+      // 代金は全額potに入り、手数料は当選者が決まるまでround_feesに記録される
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        splLotteryAddress
+      );
+      if (
+        (await tokenBalance(potAddress)) !== TICKET_PRICE * SPL_TICKETS ||
+        (await tokenBalance(buyerTokenAccount)) !==
+          INITIAL_BALANCE - TICKET_PRICE * SPL_TICKETS ||
+        lotteryConfig.lotteryPotAmount.toNumber() !==
+          TICKET_PRICE * SPL_TICKETS - fee ||
+        lotteryConfig.roundFees.toNumber() !== fee
+      ) {
+        throw new Error("token payment was not collected into the pot");
+      }
+    });
+
+    it("Is paying token prizes and fees from the pot", async () => {
+      await waitForSlot(splEndSlot + 1);
+      await drawWithMockRandomness(splLotteryAddress, Array(32).fill(9));
+
+      // 賞金は受け取る本人が所有するトークンアカウントにしか送れない
+      const stranger = anchor.web3.Keypair.generate();
+      const strangerTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          connection,
+          wallet.payer,
+          paymentMint,
+          stranger.publicKey
+        )
+      ).address;
+      try {
+        await program.methods
+          .claimCompactPrize(0, new anchor.BN(0))
+          .accounts({
+            tokenLottery: splLotteryAddress,
+            payment: paymentAccounts(strangerTokenAccount),
+          })
+          .rpc();
+        throw new Error("a prize was paid to someone else's token account");
+      } catch (err) {
+        if (!err.toString().includes("InvalidPaymentAccount")) {
+          throw err;
+        }
+      }
+
+      // 全てのチケットが1つのTicketアカウントに含まれるので、全ての賞を受け取れる
+      const drawn = await program.account.tokenLottery.fetch(splLotteryAddress);
+      for (let tier = 0; tier < drawn.winningTicketIds.length; tier++) {
+        await program.methods
+          .claimCompactPrize(tier, new anchor.BN(0))
+          .accounts({
+            tokenLottery: splLotteryAddress,
+            payment: paymentAccounts(buyerTokenAccount),
+          })
+          .rpc();
+      }
+
+      // 賞金プール全体が当選者に支払われ、potには手数料だけが残る
+      if (
+        (await tokenBalance(buyerTokenAccount)) !==
+          INITIAL_BALANCE - TICKET_PRICE * SPL_TICKETS + drawn.prizePool.toNumber() ||
+        (await tokenBalance(potAddress)) !== fee
+      ) {
+        throw new Error("token prizes were not paid from the pot");
+      }
+
+      // fee_recipient（ここではproviderのウォレット）のトークンアカウントに手数料を送る
+      await program.methods
+        .withdrawFees()
+        .accounts({
+          tokenLottery: splLotteryAddress,
+          feeRecipient: wallet.publicKey,
+          payment: paymentAccounts(buyerTokenAccount),
+        })
+        .rpc();
+
+      if (
+        (await tokenBalance(potAddress)) !== 0 ||
+        (await tokenBalance(buyerTokenAccount)) !== INITIAL_BALANCE
+      ) {
+        throw new Error("token fees were not withdrawn from the pot");
+      }
+    });
+  });
+
+  describe("purchase limits", () => {
+    const limitLotteryId = new anchor.BN(Date.now() + 4);
+    const limitLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        limitLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const LIMIT_MAX_TICKETS = 4;
+    const LIMIT_PER_WALLET = 2;
+
+    async function buyCompactTicketAs(
+      buyer: anchor.web3.Keypair,
+      quantity: number
+    ) {
+      await program.methods
+        .buyCompactTicket(new anchor.BN(quantity))
+        .accounts({ payer: buyer.publicKey, tokenLottery: limitLotteryAddress })
+        .signers([buyer])
+        .rpc();
+    }
+
+    it("Is initializing a lottery with purchase limits", async () => {
+      const slot = await connection.getSlot();
+      await program.methods
+        .initializeConfig(
+          limitLotteryId,
+          lotteryConfig(slot + 1000, {
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+            maxTickets: new anchor.BN(LIMIT_MAX_TICKETS),
+            maxTicketsPerWallet: new anchor.BN(LIMIT_PER_WALLET),
+          })
+        )
+        .rpc();
     });
 
-    const winningMint = anchor.web3.PublicKey.findProgramAddressSync(
-      [new anchor.BN(lotteryConfig.winner).toArrayLike(Buffer, "le", 8)],
+    it("Cannot buy more tickets than the per-wallet limit", async () => {
+      await buyCompactTicketAs(wallet.payer, LIMIT_PER_WALLET);
+
+      try {
+        await buyCompactTicketAs(wallet.payer, 1);
+        throw new Error("bought more tickets than the per-wallet limit");
+      } catch (err) {
+        if (!err.toString().includes("WalletTicketLimitExceeded")) {
+          throw err;
+        }
+      }
+    });
+
+    it("Cannot buy more tickets than the lottery's limit", async () => {
+      const buyer = anchor.web3.Keypair.generate();
+      await airdrop(buyer.publicKey);
+      await buyCompactTicketAs(buyer, LIMIT_MAX_TICKETS - LIMIT_PER_WALLET);
+
+      // ウォレットごとの上限には達していないが、抽選全体の上限に達している
+      const lateBuyer = anchor.web3.Keypair.generate();
+      await airdrop(lateBuyer.publicKey);
+      try {
+        await buyCompactTicketAs(lateBuyer, 1);
+        throw new Error("bought more tickets than the lottery's limit");
+      } catch (err) {
+        if (!err.toString().includes("MaxTicketsExceeded")) {
+          throw err;
+        }
+      }
+    });
+  });
+
+  describe("cancellation", () => {
+    // 乱数が解決されないまま中止までの猶予期間を過ぎた抽選は、チケット代金を全額返金する
+    const cancelLotteryId = new anchor.BN(Date.now() + 5);
+    const cancelLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        cancelLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const cancelTicketAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("ticket"),
+        cancelLotteryAddress.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
       program.programId
     )[0];
-    console.log("Winning mint", winningMint.toBase58());
+    // mock-randomnessビルドのCANCEL_TIMEOUT_SLOTS
+    const CANCEL_TIMEOUT_SLOTS = 40;
+    const CANCEL_TICKETS = 2;
+    const buyer = anchor.web3.Keypair.generate();
+    let cancelEndSlot: number;
 
-    const winningTokenAddress = getAssociatedTokenAddressSync(
-      winningMint,
-      wallet.publicKey
-    );
-    console.log("Winning token address", winningTokenAddress.toBase58());
+    it("Is cancelling a lottery whose winners were never drawn", async () => {
+      const slot = await connection.getSlot();
+      cancelEndSlot = slot + 20;
 
-    const claimIx = await program.methods
-      .claimPrize()
-      .accounts({
-        tokenProgram: TOKEN_PROGRAM_ID,
-      })
-      .instruction();
+      await program.methods
+        .initializeConfig(
+          cancelLotteryId,
+          lotteryConfig(cancelEndSlot, {
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+          })
+        )
+        .rpc();
 
-    const blockhashContext = await connection.getLatestBlockhash();
+      await airdrop(buyer.publicKey);
+      await program.methods
+        .buyCompactTicket(new anchor.BN(CANCEL_TICKETS))
+        .accounts({ payer: buyer.publicKey, tokenLottery: cancelLotteryAddress })
+        .signers([buyer])
+        .rpc();
 
-    const claimTx = new anchor.web3.Transaction({
-      blockhash: blockhashContext.blockhash,
-      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
-      feePayer: wallet.payer.publicKey,
-    }).add(claimIx);
+      // 猶予期間中は中止できない
+      await waitForSlot(cancelEndSlot + 1);
+      try {
+        await program.methods
+          .cancelLottery()
+          .accounts({ tokenLottery: cancelLotteryAddress })
+          .rpc();
+        throw new Error("cancelled before the timeout");
+      } catch (err) {
+        if (!err.toString().includes("CancelTimeoutNotReached")) {
+          throw err;
+        }
+      }
 
-    const claimSig = await anchor.web3.sendAndConfirmTransaction(
-      connection,
-      claimTx,
-      [wallet.payer]
-    );
-    console.log(claimSig);
+      await waitForSlot(cancelEndSlot + CANCEL_TIMEOUT_SLOTS + 1);
+      await program.methods
+        .cancelLottery()
+        .accounts({ tokenLottery: cancelLotteryAddress })
+        .rpc();
+
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        cancelLotteryAddress
+      );
+      if (!lotteryConfig.cancelled) {
+        throw new Error("lottery was not cancelled");
+      }
+    });
+
+    it("Is refunding a compact ticket of a cancelled lottery", async () => {
+      // Ticketアカウントは閉じられ、レントも購入者に戻る
+      const ticketRent = (await connection.getAccountInfo(cancelTicketAddress))
+        .lamports;
+      const before = await connection.getBalance(buyer.publicKey);
+
+      await program.methods
+        .refundCompactTicket(new anchor.BN(0))
+        .accounts({ payer: buyer.publicKey, tokenLottery: cancelLotteryAddress })
+        .signers([buyer])
+        .rpc();
+
+      // 手数料を含むチケット代金を全額返金する（トランザクション手数料はproviderのウォレットが支払う）
+      const after = await connection.getBalance(buyer.publicKey);
+      if (after - before !== TICKET_PRICE * CANCEL_TICKETS + ticketRent) {
+        throw new Error(`refunded ${after - before} lamports`);
+      }
+
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        cancelLotteryAddress
+      );
+      if (
+        !lotteryConfig.lotteryPotAmount.isZero() ||
+        !lotteryConfig.roundFees.isZero() ||
+        !lotteryConfig.roundPot.isZero()
+      ) {
+        throw new Error("refund was not recorded");
+      }
+
+      // Ticketアカウントが閉じられるので、同じチケットで二重に返金できない
+      try {
+        await program.methods
+          .refundCompactTicket(new anchor.BN(0))
+          .accounts({
+            payer: buyer.publicKey,
+            tokenLottery: cancelLotteryAddress,
+          })
+          .signers([buyer])
+          .rpc();
+        throw new Error("a ticket was refunded twice");
+      } catch (err) {
+        if (!err.toString().includes("AccountNotInitialized")) {
+          throw err;
+        }
+      }
+    });
+  });
+
+  describe("withdrawing a cancelled pot", () => {
+    // 中止された抽選の賞金プールのうち、返金に使わない分（ここではNoLossモードの利回り）は
+    // authorityが引き出す
+    const potLotteryId = new anchor.BN(Date.now() + 6);
+    const potLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        potLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const yieldSourceAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("yield_source"), potLotteryAddress.toBuffer()],
+      program.programId
+    )[0];
+    // mock-randomnessビルドのCANCEL_TIMEOUT_SLOTS
+    const CANCEL_TIMEOUT_SLOTS = 40;
+    const REWARD_RATE_BPS = 100;
+    // トランザクション手数料の影響を受けずに残高を比べられるよう、providerとは別のauthorityを使う
+    const authority = anchor.web3.Keypair.generate();
+    let potEndSlot: number;
+
+    it("Is cancelling a no-loss lottery after harvesting the yield", async () => {
+      await airdrop(authority.publicKey);
+      const slot = await connection.getSlot();
+      potEndSlot = slot + 20;
+
+      await program.methods
+        .initializeConfig(
+          potLotteryId,
+          lotteryConfig(potEndSlot, {
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+            prizeMode: { noLoss: {} },
+          })
+        )
+        .accounts({ payer: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .initYieldSource(REWARD_RATE_BPS)
+        .accounts({ payer: authority.publicKey, tokenLottery: potLotteryAddress })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .fundYieldSource(new anchor.BN(TICKET_PRICE))
+        .accounts({ yieldSource: yieldSourceAddress })
+        .rpc();
+
+      await program.methods
+        .buyCompactTicket(new anchor.BN(2))
+        .accounts({ tokenLottery: potLotteryAddress })
+        .rpc();
+
+      await waitForSlot(potEndSlot + 1);
+      await program.methods
+        .harvestYield()
+        .accounts({ tokenLottery: potLotteryAddress })
+        .rpc();
+
+      await waitForSlot(potEndSlot + CANCEL_TIMEOUT_SLOTS + 1);
+      await program.methods
+        .cancelLottery()
+        .accounts({ tokenLottery: potLotteryAddress })
+        .rpc();
+    });
+
+    it("Is withdrawing the cancelled pot as the authority", async () => {
+      // authority以外は引き出せない
+      try {
+        await program.methods
+          .withdrawCancelledPot()
+          .accounts({
+            authority: wallet.publicKey,
+            tokenLottery: potLotteryAddress,
+          })
+          .rpc();
+        throw new Error("a stranger withdrew the cancelled pot");
+      } catch (err) {
+        if (!err.toString().includes("NotAuthorized")) {
+          throw err;
+        }
+      }
+
+      const earned = Math.floor((TICKET_PRICE * 2 * REWARD_RATE_BPS) / 10000);
+      const fee = Math.floor((earned * FEE_BPS) / 10000);
+      const before = await connection.getBalance(authority.publicKey);
+
+      await program.methods
+        .withdrawCancelledPot()
+        .accounts({
+          authority: authority.publicKey,
+          tokenLottery: potLotteryAddress,
+        })
+        .signers([authority])
+        .rpc();
+
+      const after = await connection.getBalance(authority.publicKey);
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        potLotteryAddress
+      );
+      if (
+        after - before !== earned - fee ||
+        !lotteryConfig.lotteryPotAmount.isZero() ||
+        lotteryConfig.deposits.toNumber() !== TICKET_PRICE * 2
+      ) {
+        throw new Error(`withdrew ${after - before} lamports`);
+      }
+
+      // 預け入れは返金のために残り、二重には引き出せない
+      try {
+        await program.methods
+          .withdrawCancelledPot()
+          .accounts({
+            authority: authority.publicKey,
+            tokenLottery: potLotteryAddress,
+          })
+          .signers([authority])
+          .rpc();
+        throw new Error("the cancelled pot was withdrawn twice");
+      } catch (err) {
+        if (!err.toString().includes("NoPotToWithdraw")) {
+          throw err;
+        }
+      }
+    });
+  });
+
+  describe("no-loss mode", () => {
+    // チケット代金は預け入れで、利回りだけが賞金になる
+    const noLossLotteryId = new anchor.BN(Date.now() + 2);
+    const noLossLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        noLossLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const REWARD_RATE_BPS = 100;
+    let noLossEndSlot: number;
+
+    it("Is depositing into a no-loss lottery", async () => {
+      const slot = await connection.getSlot();
+      noLossEndSlot = slot + 30;
+
+      await program.methods
+        .initializeConfig(
+          noLossLotteryId,
+          lotteryConfig(noLossEndSlot, {
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+            prizeMode: { noLoss: {} },
+          })
+        )
+        .rpc();
+
+      await program.methods
+        .initYieldSource(REWARD_RATE_BPS)
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      const yieldSourceAddress = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("yield_source"), noLossLotteryAddress.toBuffer()],
+        program.programId
+      )[0];
+      await program.methods
+        .fundYieldSource(new anchor.BN(TICKET_PRICE))
+        .accounts({ yieldSource: yieldSourceAddress })
+        .rpc();
+
+      await program.methods
+        .buyCompactTicket(new anchor.BN(2))
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      // 預け入れは賞金プールに入らず、手数料も差し引かれない
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      if (
+        lotteryConfig.deposits.toNumber() !== TICKET_PRICE * 2 ||
+        !lotteryConfig.lotteryPotAmount.isZero() ||
+        !lotteryConfig.feesCollected.isZero()
+      ) {
+        throw new Error("deposit was not recorded");
+      }
+
+      // 抽選が終わるまでは引き出せない
+      try {
+        await program.methods
+          .refundCompactTicket(new anchor.BN(0))
+          .accounts({ tokenLottery: noLossLotteryAddress })
+          .rpc();
+        throw new Error("deposit was withdrawn before the draw");
+      } catch (err) {
+        if (!err.toString().includes("DepositLocked")) {
+          throw err;
+        }
+      }
+    });
+
+    it("Is harvesting yield and drawing a no-loss lottery", async () => {
+      await waitForSlot(noLossEndSlot + 1);
+
+      await program.methods
+        .harvestYield()
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      // 利回りから手数料を差し引いた額が賞金プールに入る
+      const earned = Math.floor((TICKET_PRICE * 2 * REWARD_RATE_BPS) / 10000);
+      const fee = Math.floor((earned * FEE_BPS) / 10000);
+      const harvested = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      if (
+        harvested.lotteryPotAmount.toNumber() !== earned - fee ||
+        harvested.feesCollected.toNumber() !== fee ||
+        !harvested.yieldHarvested
+      ) {
+        throw new Error("yield was not harvested");
+      }
+
+      // 1ラウンドにつき1回だけ
+      try {
+        await program.methods
+          .harvestYield()
+          .accounts({ tokenLottery: noLossLotteryAddress })
+          .rpc();
+        throw new Error("yield was harvested twice");
+      } catch (err) {
+        if (!err.toString().includes("YieldAlreadyHarvested")) {
+          throw err;
+        }
+      }
+
+      await drawWithMockRandomness(noLossLotteryAddress, Array(32).fill(5));
+
+      const drawn = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      if (
+        !drawn.winnerChosen ||
+        drawn.prizePool.toNumber() !== earned - fee
+      ) {
+        throw new Error("winners were not drawn from the yield");
+      }
+    });
+
+    it("Cannot withdraw a winning deposit before claiming the prize", async () => {
+      // 全てのチケットが1つのTicketアカウントに含まれるので、このチケットが当選している
+      try {
+        await program.methods
+          .refundCompactTicket(new anchor.BN(0))
+          .accounts({ tokenLottery: noLossLotteryAddress })
+          .rpc();
+        throw new Error("a winning deposit was withdrawn before claiming");
+      } catch (err) {
+        if (!err.toString().includes("PrizeNotClaimed")) {
+          throw err;
+        }
+      }
+
+      const drawn = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      for (let tier = 0; tier < drawn.winningTicketIds.length; tier++) {
+        await program.methods
+          .claimCompactPrize(tier, new anchor.BN(0))
+          .accounts({ tokenLottery: noLossLotteryAddress })
+          .rpc();
+      }
+
+      const claimed = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      if (
+        !claimed.lotteryPotAmount.isZero() ||
+        claimed.deposits.toNumber() !== TICKET_PRICE * 2
+      ) {
+        throw new Error("prizes were not paid from the yield only");
+      }
+    });
+
+    it("Is harvesting yield only from the current round's deposits", async () => {
+      const slot = await connection.getSlot();
+      const roundEndSlot = slot + 30;
+
+      await program.methods
+        .startNextRound(new anchor.BN(slot), new anchor.BN(roundEndSlot))
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      // 前のラウンドの預け入れ（チケット0〜1）は引き出さずに残しておく
+      await program.methods
+        .buyCompactTicket(new anchor.BN(1))
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      await waitForSlot(roundEndSlot + 1);
+      await program.methods
+        .harvestYield()
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      const earned = Math.floor((TICKET_PRICE * REWARD_RATE_BPS) / 10000);
+      const fee = Math.floor((earned * FEE_BPS) / 10000);
+      const harvested = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      if (
+        harvested.deposits.toNumber() !== TICKET_PRICE * 3 ||
+        harvested.lotteryPotAmount.toNumber() !== earned - fee
+      ) {
+        throw new Error("yield included the previous round's deposits");
+      }
+    });
+
+    it("Is withdrawing a previous round's deposit", async () => {
+      await program.methods
+        .refundCompactTicket(new anchor.BN(0))
+        .accounts({ tokenLottery: noLossLotteryAddress })
+        .rpc();
+
+      // Ticketアカウントが閉じられるので、同じチケットで二重に引き出せない
+      const ticketAddress = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("ticket"),
+          noLossLotteryAddress.toBuffer(),
+          new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+      const ticket = await program.account.ticket.fetchNullable(ticketAddress);
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        noLossLotteryAddress
+      );
+      // 前のラウンドの預け入れなので、現在のラウンドの預け入れは変わらない
+      if (
+        ticket !== null ||
+        lotteryConfig.deposits.toNumber() !== TICKET_PRICE ||
+        lotteryConfig.roundDeposits.toNumber() !== TICKET_PRICE
+      ) {
+        throw new Error("deposit was not withdrawn");
+      }
+    });
+  });
+
+  describe("commit-reveal randomness", () => {
+    // オラクルを使わず、購入者がコミットしたシードから当選者を決める
+    const seedLotteryId = new anchor.BN(Date.now() + 3);
+    const seedLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        seedLotteryId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    // mock-randomnessビルドのSEED_REVEAL_WINDOW_SLOTS
+    const SEED_REVEAL_WINDOW_SLOTS = 20;
+    const SEED_TICKETS = 3;
+    const seed = Array.from(anchor.web3.Keypair.generate().publicKey.toBytes());
+    let seedEndSlot: number;
+
+    it("Is committing a seed", async () => {
+      const slot = await connection.getSlot();
+      seedEndSlot = slot + 30;
+
+      await program.methods
+        .initializeConfig(
+          seedLotteryId,
+          lotteryConfig(seedEndSlot, {
+            ticketMode: { compact: {} },
+            keeperTip: new anchor.BN(0),
+            randomnessMode: { commitReveal: {} },
+          })
+        )
+        .rpc();
+
+      // シードをコミットできるのはチケットの購入者のみ
+      await program.methods
+        .buyCompactTicket(new anchor.BN(SEED_TICKETS))
+        .accounts({ tokenLottery: seedLotteryAddress })
+        .rpc();
+
+      const seedHash = Array.from(
+        createHash("sha256").update(Buffer.from(seed)).digest()
+      );
+      await program.methods
+        .commitSeed(seedHash)
+        .accounts({ tokenLottery: seedLotteryAddress })
+        .rpc();
+
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        seedLotteryAddress
+      );
+      if (lotteryConfig.seedsCommitted.toNumber() !== 1) {
+        throw new Error("seed was not committed");
+      }
+    });
+
+    it("Cannot reveal a seed that does not match the commitment", async () => {
+      await waitForSlot(seedEndSlot + 1);
+
+      const wrongSeed = seed.map((byte) => byte ^ 0xff);
+      try {
+        await program.methods
+          .revealSeed(wrongSeed)
+          .accounts({ tokenLottery: seedLotteryAddress })
+          .rpc();
+        throw new Error("a wrong seed was revealed");
+      } catch (err) {
+        if (!err.toString().includes("SeedHashMismatch")) {
+          throw err;
+        }
+      }
+    });
+
+    it("Is revealing a seed and drawing winners from it", async () => {
+      await program.methods
+        .revealSeed(seed)
+        .accounts({ tokenLottery: seedLotteryAddress })
+        .rpc();
+
+      // 公開期間中はまだ当選者を決められない
+      try {
+        await program.methods
+          .revealWinnerFromSeeds()
+          .accounts({ tokenLottery: seedLotteryAddress })
+          .rpc();
+        throw new Error("winners were drawn during the reveal window");
+      } catch (err) {
+        if (!err.toString().includes("LotteryNotCompleted")) {
+          throw err;
+        }
+      }
+
+      await waitForSlot(seedEndSlot + SEED_REVEAL_WINDOW_SLOTS + 1);
+      await program.methods
+        .revealWinnerFromSeeds()
+        .accounts({ tokenLottery: seedLotteryAddress })
+        .rpc();
+
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        seedLotteryAddress
+      );
+      if (
+        !lotteryConfig.winnerChosen ||
+        lotteryConfig.seedsRevealed.toNumber() !== 1 ||
+        lotteryConfig.winningTicketIds.length !== SEED_TICKETS ||
+        lotteryConfig.winningTicketIds.some(
+          (id) => id.toNumber() >= SEED_TICKETS
+        )
+      ) {
+        throw new Error("winners were not drawn from the seeds");
+      }
+    });
   });
 });
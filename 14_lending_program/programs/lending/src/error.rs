@@ -19,5 +19,29 @@ pub enum ErrorCode {
     #[msg("Math overflow occurred.")]
     MathOverflow,
     #[msg("Invalid price: price must be positive.")]
-    InvalidPrice
+    InvalidPrice,
+    #[msg("Price feed does not match the requested feed id.")]
+    InvalidPriceFeed,
+    #[msg("Price is older than the maximum allowed age.")]
+    StalePrice,
+    #[msg("User must be flagged as unhealthy before an auction liquidation.")]
+    NotFlaggedUnhealthy,
+    #[msg("Borrowed amount exceeds the delegated borrow allowance.")]
+    OverBorrowAllowance,
+    #[msg("Invalid term loan duration: must be greater than zero.")]
+    InvalidTermLoanDuration,
+    #[msg("Term loan has not reached maturity.")]
+    TermLoanNotMatured,
+    #[msg("User still has open deposits or borrows.")]
+    UserHasOpenPositions,
+    #[msg("Mint is not the designated quote mint.")]
+    InvalidQuoteMint,
+    #[msg("User has no variable-rate debt in the borrowed asset to liquidate.")]
+    NoVariableDebt,
+    #[msg("Mint does not match the user's recorded deposit mint.")]
+    UnsupportedMint,
+    #[msg("Collateral mint is not backing the user's borrows.")]
+    InvalidCollateral,
+    #[msg("Signer is not the program's upgrade authority.")]
+    NotUpgradeAuthority
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::error::ErrorCode;
+use crate::program::LendingProtocol;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -28,12 +30,57 @@ pub struct InitBank<'info> {
     pub system_program: Program <'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// 見積もり通貨（USDC）のMintアカウント
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    /// 見積もり通貨のBank（登録済みであること）
+    #[account(
+        seeds = [usdc_mint.key().as_ref()],
+        bump,
+        constraint = usdc_bank.mint_address == usdc_mint.key(),
+    )]
+    pub usdc_bank: Account<'info, Bank>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    /// init_bankは誰でも呼び出せるため、設定はプログラムのupgrade authorityのみが作成できる
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, LendingProtocol>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(signer.key())
+            @ ErrorCode::NotUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program <'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct InitUser<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
-    /// USDCのMintアカウント（検証済みのアドレスを取得するため）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    /// USDCのMintアカウント（設定された見積もり通貨のMintのみ受け付ける）
+    #[account(address = config.usdc_mint @ ErrorCode::InvalidQuoteMint)]
     pub usdc_mint: InterfaceAccount<'info, Mint>,
+    /// USDCのBank（登録済みであること）
+    #[account(
+        seeds = [usdc_mint.key().as_ref()],
+        bump,
+        constraint = usdc_bank.mint_address == usdc_mint.key(),
+    )]
+    pub usdc_bank: Account<'info, Bank>,
     #[account(
         init,
         payer = signer,
@@ -45,6 +92,20 @@ pub struct InitUser<'info> {
     pub system_program: Program <'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CloseUser<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [signer.key().as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn process_init_bank(
     ctx: Context<InitBank>,
     liquidation_threshold: u64,
@@ -52,6 +113,9 @@ pub fn process_init_bank(
     liquidation_bonus: u64,
     liquidation_close_factor: u64,
     interest_rate: u64,
+    liquidation_auction_duration: u64,
+    term_interest_rate: u64,
+    term_penalty_rate: u64,
 ) -> Result<()> {
     let bank = &mut ctx.accounts.bank;
 
@@ -64,15 +128,19 @@ pub fn process_init_bank(
     bank.max_ltv = max_ltv;                              // 例: 70 = 70%
     bank.liquidation_bonus = liquidation_bonus;          // 例: 5 = 5%ボーナス
     bank.liquidation_close_factor = liquidation_close_factor; // 例: 50 = 50%清算
+    bank.liquidation_auction_duration = liquidation_auction_duration; // 例: 3600 = 1時間でボーナス最大（0 = 固定ボーナス）
 
     // 利率設定
     bank.interest_rate = interest_rate;                  // 例: 500 = 年利5%
+    bank.term_interest_rate = term_interest_rate;        // 例: 800 = 固定期間ローン年利8%
+    bank.term_penalty_rate = term_penalty_rate;          // 例: 2000 = 満期後のペナルティ年利20%
 
     // 初期値（預金・借入は0から開始）
     bank.total_deposits = 0;
     bank.total_deposit_shares = 0;
     bank.total_borrowed = 0;
     bank.total_borrowed_shares = 0;
+    bank.total_term_borrowed = 0;
 
     // タイムスタンプ
     bank.last_updated = Clock::get()?.unix_timestamp;
@@ -80,14 +148,39 @@ pub fn process_init_bank(
     Ok(())
 }
 
+pub fn process_init_config(ctx: Context<InitConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.authority = ctx.accounts.signer.key();
+    config.usdc_mint = ctx.accounts.usdc_mint.key();
+
+    Ok(())
+}
+
 pub fn process_init_user(ctx: Context<InitUser>) -> Result<()> {
     let user = &mut ctx.accounts.user_account;
     user.owner = ctx.accounts.signer.key();
-    // Mintアカウントから検証済みのUSDCアドレスを取得
+    // 検証済みのUSDCアドレスを記録
     user.usdc_address = ctx.accounts.usdc_mint.key();
 
     let now = Clock::get()?.unix_timestamp;
     user.last_updated = now;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// すべての預金・借入がゼロの場合のみ、Userアカウントを閉じてrentを回収する
+pub fn process_close_user(ctx: Context<CloseUser>) -> Result<()> {
+    let user = &ctx.accounts.user_account;
+
+    require!(
+        user.deposited_sol_shares == 0
+            && user.deposited_usdc_shares == 0
+            && user.borrowed_sol_shares == 0
+            && user.borrowed_usdc_shares == 0
+            && user.term_borrowed_sol == 0
+            && user.term_borrowed_usdc == 0,
+        ErrorCode::UserHasOpenPositions
+    );
+
+    Ok(())
+}
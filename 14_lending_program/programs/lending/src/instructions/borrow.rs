@@ -1,8 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
-use crate::constants::{MAXIMUM_AGE, SOL_USD_FEED_ID, USDC_USD_FEED_ID, INTEREST_RATE_DECIMALS, SECONDS_PER_YEAR};
+use crate::constants::{SOL_USD_FEED_ID, USDC_USD_FEED_ID, INTEREST_RATE_DECIMALS, SECONDS_PER_YEAR};
+use crate::oracle::{PriceFeed, PriceOracle};
 use crate::state::*;
 use crate::error::ErrorCode;
 
@@ -37,7 +37,7 @@ pub struct Borrow<'info> {
         associated_token::token_program = token_program,
     )]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>, 
-    pub price_update: Account<'info, PriceUpdateV2>,
+    pub price_update: Account<'info, PriceFeed>,
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -53,19 +53,53 @@ pub fn process_borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
     // ゼロ額のチェック
     require!(amount > 0, ErrorCode::InvalidAmount);
 
-    // ユーザーが借入に十分な担保を持っているか確認
     let bank = &mut ctx.accounts.bank;
     let user = &mut ctx.accounts.user_account;
+    let mint_key = ctx.accounts.mint.key();
+
+    // ユーザーが借入に十分な担保を持っているか確認
+    check_borrow_capacity(bank, user, mint_key, &ctx.accounts.price_update, amount)?;
+
+    let transfer_cpi_accounts = TransferChecked {
+        from: ctx.accounts.bank_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.user_token_account.to_account_info(),
+        authority: ctx.accounts.bank_token_account.to_account_info(),
+    };
+
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let signer_seeds: &[&[&[u8]]] = &[
+        &[
+            b"treasury",
+            mint_key.as_ref(),
+            &[ctx.bumps.bank_token_account],
+        ],
+    ];
+    let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts).with_signer(signer_seeds);
+    let decimals = ctx.accounts.mint.decimals;
+
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
 
-    let price_update = &mut ctx.accounts.price_update;
+    record_borrow(bank, user, mint_key, amount)?;
 
+    Ok(())
+}
+
+/// ユーザーの担保で追加の借入が可能かどうかを確認
+/// 借入アセットと反対側のアセットを担保として評価する
+pub(crate) fn check_borrow_capacity(
+    bank: &Bank,
+    user: &User,
+    mint: Pubkey,
+    price_update: &PriceFeed,
+    amount: u64,
+) -> Result<()> {
     let total_collateral: u64;
 
-    match ctx.accounts.mint.to_account_info().key() {
+    match mint {
         key if key == user.usdc_address => {
             // USDCを借りる場合、担保はSOL
-            let sol_feed_id = get_feed_id_from_hex(SOL_USD_FEED_ID)?;
-            let sol_price = price_update.get_price_no_older_than(&Clock::get()?, MAXIMUM_AGE, &sol_feed_id)?;
+            let sol_price = price_update.get_price(&Clock::get()?, SOL_USD_FEED_ID)?;
 
             // 負の価格をチェック
             require!(sol_price.price > 0, ErrorCode::InvalidPrice);
@@ -88,8 +122,7 @@ pub fn process_borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
         },
         _ => {
             // SOLを借りる場合、担保はUSDC
-            let usdc_feed_id = get_feed_id_from_hex(USDC_USD_FEED_ID)?;
-            let usdc_price = price_update.get_price_no_older_than(&Clock::get()?, MAXIMUM_AGE, &usdc_feed_id)?;
+            let usdc_price = price_update.get_price(&Clock::get()?, USDC_USD_FEED_ID)?;
 
             // 負の価格をチェック
             require!(usdc_price.price > 0, ErrorCode::InvalidPrice);
@@ -112,11 +145,11 @@ pub fn process_borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
         .checked_mul(bank.max_ltv)
         .ok_or(ErrorCode::MathOverflow)?;
 
-    // このアセットに対するユーザーの既存借入額を取得
-    let existing_borrowed = match ctx.accounts.mint.to_account_info().key() {
-        key if key == user.usdc_address => user.borrowed_usdc,
-        _ => user.borrowed_sol,
-    };
+    // このアセットに対するユーザーの既存借入額を取得（固定期間ローンの元本を含む）
+    let existing_borrowed = match mint {
+        key if key == user.usdc_address => user.borrowed_usdc.checked_add(user.term_borrowed_usdc),
+        _ => user.borrowed_sol.checked_add(user.term_borrowed_sol),
+    }.ok_or(ErrorCode::MathOverflow)?;
 
     // 新規借入 + 既存借入が借入可能額を超えていないかチェック
     let total_borrow_after = existing_borrowed
@@ -125,29 +158,13 @@ pub fn process_borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
 
     if borrowable_amount < total_borrow_after {
         return Err(ErrorCode::OverBorrowableAmount.into());
-    }       
-
-    let transfer_cpi_accounts = TransferChecked {
-        from: ctx.accounts.bank_token_account.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.user_token_account.to_account_info(),
-        authority: ctx.accounts.bank_token_account.to_account_info(),
-    };
-
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let mint_key = ctx.accounts.mint.key();
-    let signer_seeds: &[&[&[u8]]] = &[
-        &[
-            b"treasury",
-            mint_key.as_ref(),
-            &[ctx.bumps.bank_token_account],
-        ],
-    ];
-    let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts).with_signer(signer_seeds);
-    let decimals = ctx.accounts.mint.decimals;
+    }
 
-    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    Ok(())
+}
 
+/// 借入シェアを計算して、ユーザーとBankの借入額を更新
+pub(crate) fn record_borrow(bank: &mut Bank, user: &mut User, mint: Pubkey, amount: u64) -> Result<()> {
     let users_shares: u64;
 
     if bank.total_borrowed == 0 {
@@ -166,7 +183,7 @@ pub fn process_borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
     bank.total_borrowed += amount;
     bank.total_borrowed_shares += users_shares; 
 
-    match ctx.accounts.mint.to_account_info().key() {
+    match mint {
         key if key == user.usdc_address => {
             user.borrowed_usdc += amount;
             user.borrowed_usdc_shares += users_shares;
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
+use crate::instructions::borrow::{check_borrow_capacity, record_borrow};
+use crate::oracle::PriceFeed;
+use crate::state::*;
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct ApproveBorrowAllowance<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// 借入能力を委任するウォレット
+    pub delegate: SystemAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [signer.key().as_ref()],
+        bump,
+    )]  
+    pub user_account: Account<'info, User>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + BorrowAllowance::INIT_SPACE,
+        seeds = [b"borrow_allowance", signer.key().as_ref(), delegate.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub borrow_allowance: Account<'info, BorrowAllowance>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowDelegated<'info> {
+    /// 委任先のウォレット
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// 借入能力を委任したウォレット
+    pub delegator: SystemAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut, 
+        seeds = [mint.key().as_ref()],
+        bump,
+    )]  
+    pub bank: Account<'info, Bank>,
+    #[account(
+        mut, 
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump, 
+    )]  
+    pub bank_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// 担保と借入が記録される委任元のUserアカウント
+    #[account(
+        mut, 
+        seeds = [delegator.key().as_ref()],
+        bump,
+    )]  
+    pub delegator_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [b"borrow_allowance", delegator.key().as_ref(), signer.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub borrow_allowance: Account<'info, BorrowAllowance>,
+    #[account( 
+        init_if_needed, 
+        payer = signer,
+        associated_token::mint = mint, 
+        associated_token::authority = signer,
+        associated_token::token_program = token_program,
+    )]
+    pub signer_token_account: InterfaceAccount<'info, TokenAccount>, 
+    pub price_update: Account<'info, PriceFeed>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// 委任先が借入できる上限額を設定する（0を設定すると委任を取り消す）
+pub fn process_approve_borrow_allowance(ctx: Context<ApproveBorrowAllowance>, amount: u64) -> Result<()> {
+    let borrow_allowance = &mut ctx.accounts.borrow_allowance;
+
+    borrow_allowance.delegator = ctx.accounts.signer.key();
+    borrow_allowance.delegate = ctx.accounts.delegate.key();
+    borrow_allowance.mint_address = ctx.accounts.mint.key();
+    borrow_allowance.remaining_amount = amount;
+
+    Ok(())
+}
+
+// 1. 委任された借入可能額の範囲内かチェック
+// 2. 委任元の担保で借入可能かチェック
+// 3. Bankのトークンアカウントから委任先のトークンアカウントへCPI転送
+// 4. 委任元のUserアカウントとBankの借入額を更新
+// 5. 残りの借入可能額を減らす
+
+pub fn process_borrow_delegated(ctx: Context<BorrowDelegated>, amount: u64) -> Result<()> {
+    // ゼロ額のチェック
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    // 委任された借入可能額を超えていないかチェック
+    require!(
+        amount <= ctx.accounts.borrow_allowance.remaining_amount,
+        ErrorCode::OverBorrowAllowance
+    );
+
+    let bank = &mut ctx.accounts.bank;
+    let delegator = &mut ctx.accounts.delegator_account;
+    let mint_key = ctx.accounts.mint.key();
+
+    // 委任元が借入に十分な担保を持っているか確認
+    check_borrow_capacity(bank, delegator, mint_key, &ctx.accounts.price_update, amount)?;
+
+    let transfer_cpi_accounts = TransferChecked {
+        from: ctx.accounts.bank_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.signer_token_account.to_account_info(),
+        authority: ctx.accounts.bank_token_account.to_account_info(),
+    };
+
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let signer_seeds: &[&[&[u8]]] = &[
+        &[
+            b"treasury",
+            mint_key.as_ref(),
+            &[ctx.bumps.bank_token_account],
+        ],
+    ];
+    let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts).with_signer(signer_seeds);
+    let decimals = ctx.accounts.mint.decimals;
+
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+
+    // 借入は委任元のUserアカウントに記録される
+    record_borrow(bank, delegator, mint_key, amount)?;
+
+    ctx.accounts.borrow_allowance.remaining_amount -= amount;
+
+    Ok(())
+}
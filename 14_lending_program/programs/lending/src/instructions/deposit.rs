@@ -2,6 +2,8 @@ use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
 use crate::error::ErrorCode;
+use crate::instructions::liquidate::reset_unhealthy_if_recovered;
+use crate::oracle::PriceFeed;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -34,6 +36,47 @@ pub struct Deposit<'info> {
         associated_token::token_program = token_program,
     )]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>, 
+    /// 預金アセットの価格フィード（flag_unhealthyの記録をリセットする場合のみ必要）
+    pub collateral_price_update: Option<Account<'info, PriceFeed>>,
+    /// 借入アセットの価格フィード（flag_unhealthyの記録をリセットする場合のみ必要）
+    pub borrowed_price_update: Option<Account<'info, PriceFeed>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct DepositFor<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut, 
+        seeds = [mint.key().as_ref()],
+        bump,
+    )]  
+    pub bank: Account<'info, Bank>,
+    #[account(
+        mut, 
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump, 
+    )]  
+    pub bank_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// 預金が記録される受益者のUserアカウント
+    #[account(
+        mut, 
+        seeds = [beneficiary.as_ref()],
+        bump,
+    )]  
+    pub beneficiary_account: Account<'info, User>,
+    #[account( 
+        mut,
+        associated_token::mint = mint, 
+        associated_token::authority = signer,
+        associated_token::token_program = token_program,
+    )]
+    pub signer_token_account: InterfaceAccount<'info, TokenAccount>, 
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -43,7 +86,7 @@ pub struct Deposit<'info> {
 // 2. Bankに追加する新しいシェアを計算
 // 3. ユーザーの預金額と預金シェアを更新
 // 4. Bankの総預金額と総預金シェアを更新
-// 5. ユーザーのヘルスファクターを更新（未実装）
+// 5. 価格フィードが渡された場合、ヘルスファクターが回復していればflag_unhealthyの記録をリセット
 
 pub fn process_deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     // ゼロ額のチェック
@@ -64,9 +107,72 @@ pub fn process_deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     // トークン転送を実行
     token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
 
-    // Bankに追加する新しいシェアを計算
-    let bank = &mut ctx.accounts.bank;
+    // SOL側のMintは本人の最初の預金でのみ記録する（deposit_forでは記録しない）
+    let mint = ctx.accounts.mint.key();
+    let user = &mut ctx.accounts.user_account;
+    if mint != user.usdc_address && user.sol_address == Pubkey::default() {
+        user.sol_address = mint;
+    }
+
+    // Bankとユーザーの預金額・預金シェアを更新
+    record_deposit(
+        &mut ctx.accounts.bank,
+        &mut ctx.accounts.user_account,
+        mint,
+        amount,
+    )?;
+
+    if let (Some(collateral_price_update), Some(borrowed_price_update)) = (
+        &ctx.accounts.collateral_price_update,
+        &ctx.accounts.borrowed_price_update,
+    ) {
+        reset_unhealthy_if_recovered(
+            &mut ctx.accounts.user_account,
+            &ctx.accounts.bank,
+            collateral_price_update,
+            borrowed_price_update,
+            &Clock::get()?,
+        )?;
+    }
+
+    Ok(())
+}
+
+// 他のユーザーのための預金（トレジャリーなどが別アカウントに資金を提供する場合）
+// トークンは署名者が支払い、預金とシェアは受益者のUserアカウントに記録される
+pub fn process_deposit_for(ctx: Context<DepositFor>, _beneficiary: Pubkey, amount: u64) -> Result<()> {
+    // ゼロ額のチェック
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    // CPI転送の設定
+    let transfer_cpi_accounts = TransferChecked {
+        from: ctx.accounts.signer_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.bank_token_account.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info(),
+    };
 
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts);
+    let decimals = ctx.accounts.mint.decimals;
+
+    // トークン転送を実行
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+
+    // Bankと受益者の預金額・預金シェアを更新
+    record_deposit(
+        &mut ctx.accounts.bank,
+        &mut ctx.accounts.beneficiary_account,
+        ctx.accounts.mint.key(),
+        amount,
+    )?;
+
+    Ok(())
+}
+
+/// 預金シェアを計算して、ユーザーとBankの預金額を更新
+fn record_deposit(bank: &mut Bank, user: &mut User, mint: Pubkey, amount: u64) -> Result<()> {
+    // Bankに追加する新しいシェアを計算
     let users_shares: u64;
 
     if bank.total_deposits == 0 {
@@ -82,18 +188,17 @@ pub fn process_deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
             .ok_or(ErrorCode::MathOverflow)?;
     }
 
-    let user = &mut ctx.accounts.user_account;
-
     // MintアドレスでアセットタイプをマッチしてUserの残高を更新
-    match ctx.accounts.mint.to_account_info().key() {
+    match mint {
         key if key == user.usdc_address => {
             user.deposited_usdc += amount;
             user.deposited_usdc_shares += users_shares;
         },
-        _ => {
+        key if key == user.sol_address => {
             user.deposited_sol += amount;
             user.deposited_sol_shares += users_shares;
-        }
+        },
+        _ => return err!(ErrorCode::UnsupportedMint),
     }
 
     // 上記のmatch文は、プロトコルに新しいアセットが追加された際に
@@ -107,4 +212,4 @@ pub fn process_deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     user.last_updated = Clock::get()?.unix_timestamp;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,8 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
-use crate::constants::{MAXIMUM_AGE, SOL_USD_FEED_ID, USDC_USD_FEED_ID};
+use crate::constants::{SOL_USD_FEED_ID, USDC_USD_FEED_ID};
+use crate::oracle::{OraclePrice, PriceFeed, PriceOracle};
 use crate::error::ErrorCode;
 use crate::state::*;
 
@@ -10,7 +10,12 @@ use crate::state::*;
 pub struct Liquidate<'info> {
     #[account(mut)]
     pub liquidator: Signer<'info>,
-    pub price_update: Account<'info, PriceUpdateV2>,
+    /// 清算される借り手のウォレット
+    pub borrower: SystemAccount<'info>,
+    /// 担保アセットの価格フィード
+    pub collateral_price_update: Account<'info, PriceFeed>,
+    /// 借入アセットの価格フィード
+    pub borrowed_price_update: Account<'info, PriceFeed>,
     pub collateral_mint: InterfaceAccount<'info, Mint>,
     pub borrowed_mint: InterfaceAccount<'info, Mint>,
     #[account(
@@ -39,8 +44,9 @@ pub struct Liquidate<'info> {
     pub borrowed_bank_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut, 
-        seeds = [liquidator.key().as_ref()],
+        seeds = [borrower.key().as_ref()],
         bump,
+        constraint = is_collateral_mint(&user_account, collateral_mint.key()) @ ErrorCode::InvalidCollateral,
     )]  
     pub user_account: Account<'info, User>,
     #[account( 
@@ -64,6 +70,29 @@ pub struct Liquidate<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct FlagUnhealthy<'info> {
+    pub signer: Signer<'info>,
+    /// 担保アセットの価格フィード
+    pub collateral_price_update: Account<'info, PriceFeed>,
+    /// 借入アセットの価格フィード
+    pub borrowed_price_update: Account<'info, PriceFeed>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [collateral_mint.key().as_ref()],
+        bump,
+    )]
+    pub collateral_bank: Account<'info, Bank>,
+    /// 担保Bankの清算閾値が使われるため、ユーザーの実際の担保であることを検証する
+    #[account(
+        mut,
+        seeds = [user_account.owner.as_ref()],
+        bump,
+        constraint = is_collateral_mint(&user_account, collateral_mint.key()) @ ErrorCode::InvalidCollateral,
+    )]
+    pub user_account: Account<'info, User>,
+}
+
 // 1. ユーザーが担保不足かどうかをチェック
 // 2. 清算額を計算
 // 3. 清算者のトークンアカウントからBankのトークンアカウントへCPI転送
@@ -71,72 +100,46 @@ pub struct Liquidate<'info> {
 // 5. 手数料と報酬を処理
 
 // 精度定数（パーセンテージ計算用: 100 = 100%）
-const PERCENTAGE_PRECISION: u64 = 100;
+pub(crate) const PERCENTAGE_PRECISION: u64 = 100;
 
 pub fn process_liquidate(ctx: Context<Liquidate>) -> Result<()> {
     let collateral_bank = &mut ctx.accounts.collateral_bank;
     let borrowed_bank = &mut ctx.accounts.borrowed_bank;
     let user = &mut ctx.accounts.user_account;
 
-    let price_update = &mut ctx.accounts.price_update;
-
-    let sol_feed_id = get_feed_id_from_hex(SOL_USD_FEED_ID)?;
-    let usdc_feed_id = get_feed_id_from_hex(USDC_USD_FEED_ID)?;
-
-    let sol_price = price_update.get_price_no_older_than(&Clock::get()?, MAXIMUM_AGE, &sol_feed_id)?;
-    let usdc_price = price_update.get_price_no_older_than(&Clock::get()?, MAXIMUM_AGE, &usdc_feed_id)?;
-
-    // 負の価格をチェック
-    require!(sol_price.price > 0, ErrorCode::InvalidPrice);
-    require!(usdc_price.price > 0, ErrorCode::InvalidPrice);
+    let clock = Clock::get()?;
+    let (sol_price, usdc_price) = load_prices(
+        ctx.accounts.collateral_mint.key(),
+        user.usdc_address,
+        &ctx.accounts.collateral_price_update,
+        &ctx.accounts.borrowed_price_update,
+        &clock,
+    )?;
 
     // 注意: 簡略化のため、利息は計算に含まれていない
 
-    // Pythの価格にexponentを適用して担保と借入の総額を計算（USD価値）
-    let sol_collateral_value = apply_price_with_exponent(
-        user.deposited_sol,
-        sol_price.price as u64,
-        sol_price.exponent
-    )?;
-    let usdc_collateral_value = apply_price_with_exponent(
-        user.deposited_usdc,
-        usdc_price.price as u64,
-        usdc_price.exponent
-    )?;
-    let total_collateral_value = sol_collateral_value
-        .checked_add(usdc_collateral_value)
-        .ok_or(ErrorCode::MathOverflow)?;
+    // 担保と借入の総額を計算（USD価値）
+    let (total_collateral_value, total_borrowed_value) =
+        calculate_position_values(user, &sol_price, &usdc_price)?;
 
-    let sol_borrowed_value = apply_price_with_exponent(
-        user.borrowed_sol,
-        sol_price.price as u64,
-        sol_price.exponent
+    let health_factor = calculate_health_factor(
+        total_collateral_value,
+        total_borrowed_value,
+        collateral_bank.liquidation_threshold,
     )?;
-    let usdc_borrowed_value = apply_price_with_exponent(
-        user.borrowed_usdc,
-        usdc_price.price as u64,
-        usdc_price.exponent
-    )?;
-    let total_borrowed_value = sol_borrowed_value
-        .checked_add(usdc_borrowed_value)
-        .ok_or(ErrorCode::MathOverflow)?;
-
-    // ゼロ除算を防ぐ（借入がない場合は清算不可）
-    require!(total_borrowed_value > 0, ErrorCode::NotUndercollateralized);
-
-    // Health factorを計算: (担保価値 * 清算閾値) / (借入価値 * 100)
-    // Health factor < 100 の場合、担保不足
-    let health_factor = (total_collateral_value as u128)
-        .checked_mul(collateral_bank.liquidation_threshold as u128)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(total_borrowed_value as u128)
-        .ok_or(ErrorCode::MathOverflow)? as u64;
 
     // Health factor >= 100 の場合、担保は十分
     if health_factor >= PERCENTAGE_PRECISION {
         return Err(ErrorCode::NotUndercollateralized.into());
     }
 
+    // 清算ボーナスを決定（オークション有効時は経過時間に応じて増加）
+    let liquidation_bonus = calculate_liquidation_bonus(
+        collateral_bank,
+        user.unhealthy_since,
+        clock.unix_timestamp,
+    )?;
+
     // 清算額を計算（借入価値の一部をトークン数量に変換）
     // liquidation_close_factor はパーセンテージ（例: 50 = 50%）
     let liquidation_value = (total_borrowed_value as u128)
@@ -162,6 +165,22 @@ pub fn process_liquidate(ctx: Context<Liquidate>) -> Result<()> {
         borrowed_token_exponent
     )?;
 
+    // 清算で返済できるのは変動金利の借入のみ（固定期間ローンはliquidate_term_loanで清算する）
+    // 借入価値には固定期間ローンの元本も含まれるため、変動金利の借入額を上限とする
+    let variable_debt = match ctx.accounts.borrowed_mint.to_account_info().key() {
+        key if key == user.usdc_address => user.borrowed_usdc,
+        _ => user.borrowed_sol,
+    };
+    require!(variable_debt > 0, ErrorCode::NoVariableDebt);
+    let liquidation_amount = liquidation_amount.min(variable_debt);
+
+    // 担保の計算は実際に返済される額の価値に基づく
+    let liquidation_value = apply_price_with_exponent(
+        liquidation_amount,
+        borrowed_token_price,
+        borrowed_token_exponent
+    )?;
+
     // 清算者が借入額をBankに返済
     let transfer_to_bank = TransferChecked {
         from: ctx.accounts.liquidator_borrowed_token_account.to_account_info(),
@@ -189,7 +208,7 @@ pub fn process_liquidate(ctx: Context<Liquidate>) -> Result<()> {
     // 清算ボーナスを含めた担保額を計算
     // liquidation_bonus はパーセンテージ（例: 5 = 5%ボーナス）
     let liquidation_value_with_bonus = (liquidation_value as u128)
-        .checked_mul((PERCENTAGE_PRECISION + collateral_bank.liquidation_bonus) as u128)
+        .checked_mul((PERCENTAGE_PRECISION + liquidation_bonus) as u128)
         .ok_or(ErrorCode::MathOverflow)?
         .checked_div(PERCENTAGE_PRECISION as u128)
         .ok_or(ErrorCode::MathOverflow)? as u64;
@@ -276,12 +295,242 @@ pub fn process_liquidate(ctx: Context<Liquidate>) -> Result<()> {
     collateral_bank.total_deposits = collateral_bank.total_deposits.saturating_sub(collateral_to_liquidator);
     collateral_bank.total_deposit_shares = collateral_bank.total_deposit_shares.saturating_sub(collateral_shares_to_remove);
 
+    // 清算後にヘルスファクターが回復していれば、flag_unhealthyの記録をリセット
+    if !is_undercollateralized(user, collateral_bank.liquidation_threshold, &sol_price, &usdc_price)? {
+        user.unhealthy_since = 0;
+    }
+
     Ok(())
 }
 
+// ダッチオークション方式の清算の開始時刻を記録する（誰でも呼び出し可能）
+// 担保不足であれば最初に検出された時刻を記録し、担保が回復していれば記録をリセットする
+pub fn process_flag_unhealthy(ctx: Context<FlagUnhealthy>) -> Result<()> {
+    let collateral_bank = &ctx.accounts.collateral_bank;
+    let user = &mut ctx.accounts.user_account;
+
+    let clock = Clock::get()?;
+    let (sol_price, usdc_price) = load_prices(
+        ctx.accounts.collateral_mint.key(),
+        user.usdc_address,
+        &ctx.accounts.collateral_price_update,
+        &ctx.accounts.borrowed_price_update,
+        &clock,
+    )?;
+
+    let is_unhealthy = is_undercollateralized(
+        user,
+        collateral_bank.liquidation_threshold,
+        &sol_price,
+        &usdc_price,
+    )?;
+
+    if !is_unhealthy {
+        user.unhealthy_since = 0;
+    } else if user.unhealthy_since == 0 {
+        // すでに記録済みの場合は上書きしない（オークションをリセットさせない）
+        user.unhealthy_since = clock.unix_timestamp;
+    }
+
+    Ok(())
+}
+
+/// ヘルスファクターが回復していれば、flag_unhealthyの記録をリセットする
+/// 価格フィードを持たない預金・返済から呼び出すため、価格の読み込みもここで行う
+pub(crate) fn reset_unhealthy_if_recovered(
+    user: &mut User,
+    collateral_bank: &Bank,
+    collateral_price_update: &PriceFeed,
+    borrowed_price_update: &PriceFeed,
+    clock: &Clock,
+) -> Result<()> {
+    if user.unhealthy_since == 0 {
+        return Ok(());
+    }
+
+    if !has_open_borrows(user) {
+        user.unhealthy_since = 0;
+        return Ok(());
+    }
+
+    require!(
+        is_collateral_mint(user, collateral_bank.mint_address),
+        ErrorCode::InvalidCollateral
+    );
+
+    let (sol_price, usdc_price) = load_prices(
+        collateral_bank.mint_address,
+        user.usdc_address,
+        collateral_price_update,
+        borrowed_price_update,
+        clock,
+    )?;
+
+    if !is_undercollateralized(user, collateral_bank.liquidation_threshold, &sol_price, &usdc_price)? {
+        user.unhealthy_since = 0;
+    }
+
+    Ok(())
+}
+
+/// 変動金利の借入または固定期間ローンが残っているか
+pub(crate) fn has_open_borrows(user: &User) -> bool {
+    user.borrowed_sol > 0
+        || user.borrowed_usdc > 0
+        || user.term_borrowed_sol > 0
+        || user.term_borrowed_usdc > 0
+}
+
+/// 担保Mintがユーザーの実際の担保かどうか
+/// そのアセットに預金があり、反対側のアセットに借入がある場合のみ担保とみなす
+pub(crate) fn is_collateral_mint(user: &User, collateral_mint: Pubkey) -> bool {
+    match collateral_mint {
+        key if key == user.usdc_address => {
+            user.deposited_usdc > 0 && (user.borrowed_sol > 0 || user.term_borrowed_sol > 0)
+        },
+        key if key == user.sol_address => {
+            user.deposited_sol > 0 && (user.borrowed_usdc > 0 || user.term_borrowed_usdc > 0)
+        },
+        _ => false,
+    }
+}
+
+/// 担保不足（Health factor < 100）かどうか。借入がない場合は担保不足ではない
+fn is_undercollateralized(
+    user: &User,
+    liquidation_threshold: u64,
+    sol_price: &OraclePrice,
+    usdc_price: &OraclePrice,
+) -> Result<bool> {
+    let (total_collateral_value, total_borrowed_value) =
+        calculate_position_values(user, sol_price, usdc_price)?;
+
+    if total_borrowed_value == 0 {
+        return Ok(false);
+    }
+
+    let health_factor = calculate_health_factor(
+        total_collateral_value,
+        total_borrowed_value,
+        liquidation_threshold,
+    )?;
+
+    Ok(health_factor < PERCENTAGE_PRECISION)
+}
+
+/// 担保側と借入側の価格フィードからSOLとUSDCの価格を取得
+/// 価格フィードは1アカウントにつき1フィードなので、担保Mintで対応を判定する
+pub(crate) fn load_prices(
+    collateral_mint: Pubkey,
+    usdc_address: Pubkey,
+    collateral_price_update: &PriceFeed,
+    borrowed_price_update: &PriceFeed,
+    clock: &Clock,
+) -> Result<(OraclePrice, OraclePrice)> {
+    let (sol_price_update, usdc_price_update) = match collateral_mint {
+        key if key == usdc_address => (borrowed_price_update, collateral_price_update),
+        _ => (collateral_price_update, borrowed_price_update),
+    };
+
+    let sol_price = sol_price_update.get_price(clock, SOL_USD_FEED_ID)?;
+    let usdc_price = usdc_price_update.get_price(clock, USDC_USD_FEED_ID)?;
+
+    // 負の価格をチェック
+    require!(sol_price.price > 0, ErrorCode::InvalidPrice);
+    require!(usdc_price.price > 0, ErrorCode::InvalidPrice);
+
+    Ok((sol_price, usdc_price))
+}
+
+/// ユーザーの担保総額と借入総額をUSD価値で計算
+fn calculate_position_values(
+    user: &User,
+    sol_price: &OraclePrice,
+    usdc_price: &OraclePrice,
+) -> Result<(u64, u64)> {
+    // Pythの価格にexponentを適用
+    let sol_collateral_value = apply_price_with_exponent(
+        user.deposited_sol,
+        sol_price.price as u64,
+        sol_price.exponent
+    )?;
+    let usdc_collateral_value = apply_price_with_exponent(
+        user.deposited_usdc,
+        usdc_price.price as u64,
+        usdc_price.exponent
+    )?;
+    let total_collateral_value = sol_collateral_value
+        .checked_add(usdc_collateral_value)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // 変動金利の借入と固定期間ローンの元本を合算して評価する
+    let sol_borrowed_value = apply_price_with_exponent(
+        user.borrowed_sol
+            .checked_add(user.term_borrowed_sol)
+            .ok_or(ErrorCode::MathOverflow)?,
+        sol_price.price as u64,
+        sol_price.exponent
+    )?;
+    let usdc_borrowed_value = apply_price_with_exponent(
+        user.borrowed_usdc
+            .checked_add(user.term_borrowed_usdc)
+            .ok_or(ErrorCode::MathOverflow)?,
+        usdc_price.price as u64,
+        usdc_price.exponent
+    )?;
+    let total_borrowed_value = sol_borrowed_value
+        .checked_add(usdc_borrowed_value)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok((total_collateral_value, total_borrowed_value))
+}
+
+/// Health factorを計算: (担保価値 * 清算閾値) / 借入価値
+/// Health factor < 100 の場合、担保不足
+fn calculate_health_factor(
+    total_collateral_value: u64,
+    total_borrowed_value: u64,
+    liquidation_threshold: u64,
+) -> Result<u64> {
+    // ゼロ除算を防ぐ（借入がない場合は清算不可）
+    require!(total_borrowed_value > 0, ErrorCode::NotUndercollateralized);
+
+    let health_factor = (total_collateral_value as u128)
+        .checked_mul(liquidation_threshold as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(total_borrowed_value as u128)
+        .ok_or(ErrorCode::MathOverflow)? as u64;
+
+    Ok(health_factor)
+}
+
+/// 現時点の清算ボーナス（パーセンテージ）を計算
+/// オークション無効（liquidation_auction_duration == 0）の場合は固定のliquidation_bonus
+/// オークション有効の場合は、flag_unhealthyで記録された時刻から
+/// liquidation_auction_duration秒かけて0からliquidation_bonusまで線形に増加する
+fn calculate_liquidation_bonus(bank: &Bank, unhealthy_since: i64, now: i64) -> Result<u64> {
+    if bank.liquidation_auction_duration == 0 {
+        return Ok(bank.liquidation_bonus);
+    }
+
+    // オークション方式では、事前にflag_unhealthyで記録されている必要がある
+    require!(unhealthy_since > 0, ErrorCode::NotFlaggedUnhealthy);
+
+    let elapsed = now.saturating_sub(unhealthy_since).max(0) as u64;
+    let elapsed = elapsed.min(bank.liquidation_auction_duration);
+
+    let bonus = (bank.liquidation_bonus as u128)
+        .checked_mul(elapsed as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(bank.liquidation_auction_duration as u128)
+        .ok_or(ErrorCode::MathOverflow)? as u64;
+
+    Ok(bonus)
+}
+
 /// Pythの価格にexponentを適用して金額を計算
 /// Pythの価格形式: actual_price = price * 10^exponent
-fn apply_price_with_exponent(amount: u64, price: u64, exponent: i32) -> Result<u64> {
+pub(crate) fn apply_price_with_exponent(amount: u64, price: u64, exponent: i32) -> Result<u64> {
     let amount_u128 = amount as u128;
     let price_u128 = price as u128;
 
@@ -301,7 +550,7 @@ fn apply_price_with_exponent(amount: u64, price: u64, exponent: i32) -> Result<u
 }
 
 /// USD価値からトークン数量に変換（価格で割る）
-fn convert_value_to_amount(value: u64, price: u64, exponent: i32) -> Result<u64> {
+pub(crate) fn convert_value_to_amount(value: u64, price: u64, exponent: i32) -> Result<u64> {
     let value_u128 = value as u128;
     let price_u128 = price as u128;
 
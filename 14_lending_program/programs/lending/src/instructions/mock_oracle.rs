@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitMockPrice<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + MockPrice::INIT_SPACE,
+        payer = signer,
+        seeds = [b"mock_price", feed_id.as_ref()],
+        bump,
+    )]
+    pub mock_price: Account<'info, MockPrice>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMockPrice<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"mock_price", mock_price.feed_id.as_ref()],
+        bump,
+        constraint = mock_price.authority == signer.key(),
+    )]
+    pub mock_price: Account<'info, MockPrice>,
+}
+
+pub fn process_init_mock_price(
+    ctx: Context<InitMockPrice>,
+    feed_id: [u8; 32],
+    price: i64,
+    expo: i32,
+    conf: u64,
+) -> Result<()> {
+    let mock_price = &mut ctx.accounts.mock_price;
+
+    mock_price.authority = ctx.accounts.signer.key();
+    mock_price.feed_id = feed_id;
+    mock_price.price = price;
+    mock_price.expo = expo;
+    mock_price.conf = conf;
+    mock_price.publish_time = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+// publish_timeも指定できるため、古い価格フィードのエラーもテストできる
+pub fn process_set_mock_price(
+    ctx: Context<SetMockPrice>,
+    price: i64,
+    expo: i32,
+    conf: u64,
+    publish_time: i64,
+) -> Result<()> {
+    let mock_price = &mut ctx.accounts.mock_price;
+
+    mock_price.price = price;
+    mock_price.expo = expo;
+    mock_price.conf = conf;
+    mock_price.publish_time = publish_time;
+
+    Ok(())
+}
@@ -9,4 +9,12 @@ pub mod withdraw;
 pub use repay::*;
 pub mod repay;
 pub use liquidate::*;
-pub mod liquidate;
\ No newline at end of file
+pub mod liquidate;
+pub use delegation::*;
+pub mod delegation;
+pub use term_loan::*;
+pub mod term_loan;
+#[cfg(feature = "mock-oracle")]
+pub use mock_oracle::*;
+#[cfg(feature = "mock-oracle")]
+pub mod mock_oracle;
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
+use crate::instructions::liquidate::{has_open_borrows, reset_unhealthy_if_recovered};
+use crate::oracle::PriceFeed;
 use crate::state::*;
 use crate::error::ErrorCode;
 
@@ -35,6 +37,12 @@ pub struct Repay<'info> {
         associated_token::token_program = token_program,
     )]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>, 
+    /// 担保アセットのBank（flag_unhealthyの記録をリセットする場合のみ必要）
+    pub collateral_bank: Option<Account<'info, Bank>>,
+    /// 担保アセットの価格フィード（flag_unhealthyの記録をリセットする場合のみ必要）
+    pub collateral_price_update: Option<Account<'info, PriceFeed>>,
+    /// 借入アセットの価格フィード（flag_unhealthyの記録をリセットする場合のみ必要）
+    pub borrowed_price_update: Option<Account<'info, PriceFeed>>,
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -105,5 +113,23 @@ pub fn process_repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
     bank.total_borrowed -= amount;
     bank.total_borrowed_shares -= shares_to_remove;
 
+    // ヘルスファクターの回復が確認できた場合、または借入をすべて返済した場合は
+    // flag_unhealthyの記録をリセットする（古い記録から次のオークションが始まらないように）
+    if let (Some(collateral_bank), Some(collateral_price_update), Some(borrowed_price_update)) = (
+        &ctx.accounts.collateral_bank,
+        &ctx.accounts.collateral_price_update,
+        &ctx.accounts.borrowed_price_update,
+    ) {
+        reset_unhealthy_if_recovered(
+            user,
+            collateral_bank,
+            collateral_price_update,
+            borrowed_price_update,
+            &Clock::get()?,
+        )?;
+    } else if !has_open_borrows(user) {
+        user.unhealthy_since = 0;
+    }
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,448 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
+use crate::constants::{INTEREST_RATE_DECIMALS, SECONDS_PER_YEAR};
+use crate::instructions::borrow::check_borrow_capacity;
+use crate::instructions::liquidate::{
+    apply_price_with_exponent, convert_value_to_amount, is_collateral_mint, load_prices,
+    PERCENTAGE_PRECISION,
+};
+use crate::oracle::PriceFeed;
+use crate::state::*;
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+#[instruction(loan_id: u64)]
+pub struct BorrowTerm<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut, 
+        seeds = [mint.key().as_ref()],
+        bump,
+    )]  
+    pub bank: Account<'info, Bank>,
+    #[account(
+        mut, 
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump, 
+    )]  
+    pub bank_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut, 
+        seeds = [signer.key().as_ref()],
+        bump,
+    )]  
+    pub user_account: Account<'info, User>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + TermLoan::INIT_SPACE,
+        seeds = [b"term_loan", signer.key().as_ref(), mint.key().as_ref(), loan_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub term_loan: Account<'info, TermLoan>,
+    #[account( 
+        init_if_needed, 
+        payer = signer,
+        associated_token::mint = mint, 
+        associated_token::authority = signer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, 
+    pub price_update: Account<'info, PriceFeed>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RepayTerm<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut, 
+        seeds = [mint.key().as_ref()],
+        bump,
+    )]  
+    pub bank: Account<'info, Bank>,
+    #[account(
+        mut, 
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump, 
+    )]  
+    pub bank_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut, 
+        seeds = [signer.key().as_ref()],
+        bump,
+    )]  
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"term_loan", signer.key().as_ref(), mint.key().as_ref(), term_loan.loan_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub term_loan: Account<'info, TermLoan>,
+    #[account( 
+        mut,
+        associated_token::mint = mint, 
+        associated_token::authority = signer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, 
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidateTermLoan<'info> {
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+    /// 借り手のウォレット（TermLoanのrentが返却される）
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    /// 担保アセットの価格フィード
+    pub collateral_price_update: Account<'info, PriceFeed>,
+    /// 借入アセットの価格フィード
+    pub borrowed_price_update: Account<'info, PriceFeed>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    /// 担保と同じアセットは指定できない（同じBankとトレジャリーが二重に渡されるため）
+    #[account(
+        constraint = borrowed_mint.key() != collateral_mint.key() @ ErrorCode::InvalidCollateral,
+    )]
+    pub borrowed_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut, 
+        seeds = [collateral_mint.key().as_ref()],
+        bump,
+    )]  
+    pub collateral_bank: Account<'info, Bank>,
+    #[account(
+        mut, 
+        seeds = [b"treasury", collateral_mint.key().as_ref()],
+        bump, 
+    )]  
+    pub collateral_bank_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut, 
+        seeds = [borrowed_mint.key().as_ref()],
+        bump,
+    )]  
+    pub borrowed_bank: Account<'info, Bank>,
+    #[account(
+        mut, 
+        seeds = [b"treasury", borrowed_mint.key().as_ref()],
+        bump, 
+    )]  
+    pub borrowed_bank_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut, 
+        seeds = [owner.key().as_ref()],
+        bump,
+        constraint = is_collateral_mint(&user_account, collateral_mint.key()) @ ErrorCode::InvalidCollateral,
+    )]  
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"term_loan", owner.key().as_ref(), borrowed_mint.key().as_ref(), term_loan.loan_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub term_loan: Account<'info, TermLoan>,
+    #[account( 
+        init_if_needed, 
+        payer = liquidator,
+        associated_token::mint = collateral_mint, 
+        associated_token::authority = liquidator,
+        associated_token::token_program = token_program,
+    )]
+    pub liquidator_collateral_token_account: InterfaceAccount<'info, TokenAccount>, 
+    #[account( 
+        init_if_needed, 
+        payer = liquidator,
+        associated_token::mint = borrowed_mint, 
+        associated_token::authority = liquidator,
+        associated_token::token_program = token_program,
+    )]
+    pub liquidator_borrowed_token_account: InterfaceAccount<'info, TokenAccount>, 
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// 1. 変動金利の借入と同じ担保チェックを行う（既存の固定期間ローンも含む）
+// 2. Bankの現在の固定利率でTermLoanを作成
+// 3. BankのトークンアカウントからユーザーのトークンアカウントへCPI転送
+// 4. ユーザーとBankの固定期間ローン元本を更新
+
+pub fn process_borrow_term(ctx: Context<BorrowTerm>, loan_id: u64, amount: u64, duration: i64) -> Result<()> {
+    // ゼロ額のチェック
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(duration > 0, ErrorCode::InvalidTermLoanDuration);
+
+    let bank = &mut ctx.accounts.bank;
+    let user = &mut ctx.accounts.user_account;
+    let mint_key = ctx.accounts.mint.key();
+
+    // ユーザーが借入に十分な担保を持っているか確認
+    check_borrow_capacity(bank, user, mint_key, &ctx.accounts.price_update, amount)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let term_loan = &mut ctx.accounts.term_loan;
+
+    // 利率は借入時点のBankの設定で固定される
+    term_loan.owner = ctx.accounts.signer.key();
+    term_loan.mint_address = mint_key;
+    term_loan.loan_id = loan_id;
+    term_loan.principal = amount;
+    term_loan.interest_rate = bank.term_interest_rate;
+    term_loan.penalty_rate = bank.term_penalty_rate;
+    term_loan.start_time = now;
+    term_loan.maturity = now.checked_add(duration).ok_or(ErrorCode::MathOverflow)?;
+
+    let transfer_cpi_accounts = TransferChecked {
+        from: ctx.accounts.bank_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.user_token_account.to_account_info(),
+        authority: ctx.accounts.bank_token_account.to_account_info(),
+    };
+
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let signer_seeds: &[&[&[u8]]] = &[
+        &[
+            b"treasury",
+            mint_key.as_ref(),
+            &[ctx.bumps.bank_token_account],
+        ],
+    ];
+    let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts).with_signer(signer_seeds);
+    let decimals = ctx.accounts.mint.decimals;
+
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+
+    // ユーザーとBankの固定期間ローン元本を更新
+    match mint_key {
+        key if key == user.usdc_address => {
+            user.term_borrowed_usdc = user.term_borrowed_usdc
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        },
+        _ => {
+            user.term_borrowed_sol = user.term_borrowed_sol
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+    }
+    bank.total_term_borrowed = bank.total_term_borrowed
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(())
+}
+
+// 元本と利息（満期後はペナルティ利息を含む）を一括で返済し、TermLoanを閉じる
+// 利息はBankの総預金に加算され、預金者に分配される
+pub fn process_repay_term(ctx: Context<RepayTerm>) -> Result<()> {
+    let term_loan = &ctx.accounts.term_loan;
+    let now = Clock::get()?.unix_timestamp;
+
+    let amount_due = calculate_term_loan_due(term_loan, now)?;
+    let interest = amount_due - term_loan.principal;
+
+    let transfer_cpi_accounts = TransferChecked {
+        from: ctx.accounts.user_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.bank_token_account.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info(),
+    };
+
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts);
+    let decimals = ctx.accounts.mint.decimals;
+
+    token_interface::transfer_checked(cpi_ctx, amount_due, decimals)?;
+
+    let bank = &mut ctx.accounts.bank;
+    let user = &mut ctx.accounts.user_account;
+
+    settle_term_loan(bank, user, term_loan, interest)?;
+
+    Ok(())
+}
+
+// 1. 満期を過ぎているかチェック
+// 2. 清算者が返済総額（元本+利息+ペナルティ）をBankに支払う
+// 3. 返済額に清算ボーナスを加えた価値の担保を清算者に転送
+// 4. ユーザーとBankの状態を更新し、TermLoanを閉じる
+
+pub fn process_liquidate_term_loan(ctx: Context<LiquidateTermLoan>) -> Result<()> {
+    let clock = Clock::get()?;
+    let term_loan = &ctx.accounts.term_loan;
+
+    // 満期後のみ清算可能
+    require!(clock.unix_timestamp > term_loan.maturity, ErrorCode::TermLoanNotMatured);
+
+    let collateral_bank = &mut ctx.accounts.collateral_bank;
+    let borrowed_bank = &mut ctx.accounts.borrowed_bank;
+    let user = &mut ctx.accounts.user_account;
+
+    let (sol_price, usdc_price) = load_prices(
+        ctx.accounts.collateral_mint.key(),
+        user.usdc_address,
+        &ctx.accounts.collateral_price_update,
+        &ctx.accounts.borrowed_price_update,
+        &clock,
+    )?;
+    let (borrowed_price, collateral_price) = match ctx.accounts.borrowed_mint.key() {
+        key if key == user.usdc_address => (&usdc_price, &sol_price),
+        _ => (&sol_price, &usdc_price),
+    };
+
+    let amount_due = calculate_term_loan_due(term_loan, clock.unix_timestamp)?;
+    let interest = amount_due - term_loan.principal;
+
+    // 返済総額のUSD価値に清算ボーナスを加え、担保トークン数量に変換
+    let due_value = apply_price_with_exponent(
+        amount_due,
+        borrowed_price.price as u64,
+        borrowed_price.exponent
+    )?;
+    let due_value_with_bonus = (due_value as u128)
+        .checked_mul((PERCENTAGE_PRECISION + collateral_bank.liquidation_bonus) as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(PERCENTAGE_PRECISION as u128)
+        .ok_or(ErrorCode::MathOverflow)? as u64;
+    let collateral_to_liquidator = convert_value_to_amount(
+        due_value_with_bonus,
+        collateral_price.price as u64,
+        collateral_price.exponent
+    )?;
+
+    // 担保が返済総額に足りない場合でも、差し引けるのはユーザーの預金額まで
+    let deposited_collateral = match ctx.accounts.collateral_mint.key() {
+        key if key == user.usdc_address => user.deposited_usdc,
+        _ => user.deposited_sol,
+    };
+    let collateral_to_liquidator = collateral_to_liquidator.min(deposited_collateral);
+
+    // 清算者が返済総額をBankに支払う
+    let transfer_to_bank = TransferChecked {
+        from: ctx.accounts.liquidator_borrowed_token_account.to_account_info(),
+        mint: ctx.accounts.borrowed_mint.to_account_info(),
+        to: ctx.accounts.borrowed_bank_token_account.to_account_info(),
+        authority: ctx.accounts.liquidator.to_account_info(),
+    };
+
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx_to_bank = CpiContext::new(cpi_program.clone(), transfer_to_bank);
+    let decimals = ctx.accounts.borrowed_mint.decimals;
+
+    token_interface::transfer_checked(cpi_ctx_to_bank, amount_due, decimals)?;
+
+    // 清算者に担保+ボーナスを転送
+    let transfer_to_liquidator = TransferChecked {
+        from: ctx.accounts.collateral_bank_token_account.to_account_info(),
+        mint: ctx.accounts.collateral_mint.to_account_info(),
+        to: ctx.accounts.liquidator_collateral_token_account.to_account_info(),
+        authority: ctx.accounts.collateral_bank_token_account.to_account_info(),
+    };
+
+    let mint_key = ctx.accounts.collateral_mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[
+        &[
+            b"treasury",
+            mint_key.as_ref(),
+            &[ctx.bumps.collateral_bank_token_account],
+        ],
+    ];
+    let cpi_ctx_to_liquidator = CpiContext::new(cpi_program, transfer_to_liquidator)
+        .with_signer(signer_seeds);
+    let collateral_decimals = ctx.accounts.collateral_mint.decimals;
+    token_interface::transfer_checked(cpi_ctx_to_liquidator, collateral_to_liquidator, collateral_decimals)?;
+
+    // 担保シェアを計算
+    let collateral_shares_to_remove = if collateral_bank.total_deposits > 0 {
+        (collateral_to_liquidator as u128)
+            .checked_mul(collateral_bank.total_deposit_shares as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(collateral_bank.total_deposits as u128)
+            .ok_or(ErrorCode::MathOverflow)? as u64
+    } else {
+        0
+    };
+
+    // ユーザーの担保額とシェアを更新
+    match ctx.accounts.collateral_mint.key() {
+        key if key == user.usdc_address => {
+            user.deposited_usdc = user.deposited_usdc.saturating_sub(collateral_to_liquidator);
+            user.deposited_usdc_shares = user.deposited_usdc_shares.saturating_sub(collateral_shares_to_remove);
+        },
+        _ => {
+            user.deposited_sol = user.deposited_sol.saturating_sub(collateral_to_liquidator);
+            user.deposited_sol_shares = user.deposited_sol_shares.saturating_sub(collateral_shares_to_remove);
+        }
+    }
+
+    // Bankの総預金額とシェアを更新
+    collateral_bank.total_deposits = collateral_bank.total_deposits.saturating_sub(collateral_to_liquidator);
+    collateral_bank.total_deposit_shares = collateral_bank.total_deposit_shares.saturating_sub(collateral_shares_to_remove);
+
+    settle_term_loan(borrowed_bank, user, term_loan, interest)?;
+
+    Ok(())
+}
+
+/// 返済総額（元本 + 利息 + ペナルティ利息）を単利で計算
+///
+/// - 借入から返済まで: 固定年利 interest_rate
+/// - 満期以降: interest_rate にペナルティ年利 penalty_rate を上乗せ
+fn calculate_term_loan_due(term_loan: &TermLoan, now: i64) -> Result<u64> {
+    let elapsed = now.saturating_sub(term_loan.start_time).max(0) as u64;
+    let overdue = now.saturating_sub(term_loan.maturity).max(0) as u64;
+
+    // interest = principal * rate * time / (INTEREST_RATE_DECIMALS * SECONDS_PER_YEAR)
+    let rate_time = (term_loan.interest_rate as u128)
+        .checked_mul(elapsed as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_add(
+            (term_loan.penalty_rate as u128)
+                .checked_mul(overdue as u128)
+                .ok_or(ErrorCode::MathOverflow)?,
+        )
+        .ok_or(ErrorCode::MathOverflow)?;
+    let interest = (term_loan.principal as u128)
+        .checked_mul(rate_time)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div((INTEREST_RATE_DECIMALS as u128) * (SECONDS_PER_YEAR as u128))
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let amount_due = (term_loan.principal as u128)
+        .checked_add(interest)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    u64::try_from(amount_due).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// 返済済みのTermLoanの元本をユーザーとBankから差し引き、利息を預金者に分配
+fn settle_term_loan(bank: &mut Bank, user: &mut User, term_loan: &TermLoan, interest: u64) -> Result<()> {
+    match term_loan.mint_address {
+        key if key == user.usdc_address => {
+            user.term_borrowed_usdc = user.term_borrowed_usdc.saturating_sub(term_loan.principal);
+        },
+        _ => {
+            user.term_borrowed_sol = user.term_borrowed_sol.saturating_sub(term_loan.principal);
+        }
+    }
+
+    bank.total_term_borrowed = bank.total_term_borrowed.saturating_sub(term_loan.principal);
+    bank.total_deposits = bank.total_deposits
+        .checked_add(interest)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ pub mod state;
 pub mod instructions;
 pub mod error;
 pub mod constants;
+pub mod oracle;
 
 pub use state::*;
 
@@ -15,6 +16,7 @@ pub mod lending_protocol {
 
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn init_bank(
         ctx: Context<InitBank>,
         liquidation_threshold: u64,
@@ -22,6 +24,9 @@ pub mod lending_protocol {
         liquidation_bonus: u64,
         liquidation_close_factor: u64,
         interest_rate: u64,
+        liquidation_auction_duration: u64,
+        term_interest_rate: u64,
+        term_penalty_rate: u64,
     ) -> Result<()> {
         process_init_bank(
             ctx,
@@ -30,17 +35,32 @@ pub mod lending_protocol {
             liquidation_bonus,
             liquidation_close_factor,
             interest_rate,
+            liquidation_auction_duration,
+            term_interest_rate,
+            term_penalty_rate,
         )
     }
 
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        process_init_config(ctx)
+    }
+
     pub fn init_user(ctx: Context<InitUser>) -> Result<()> {
         process_init_user(ctx)
     }
 
+    pub fn close_user(ctx: Context<CloseUser>) -> Result<()> {
+        process_close_user(ctx)
+    }
+
     pub fn deposit (ctx: Context<Deposit>, amount: u64) -> Result<()> {
         process_deposit(ctx, amount)
     }
 
+    pub fn deposit_for(ctx: Context<DepositFor>, beneficiary: Pubkey, amount: u64) -> Result<()> {
+        process_deposit_for(ctx, beneficiary, amount)
+    }
+
     pub fn withdraw (ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         process_withdraw(ctx, amount)
     }
@@ -49,6 +69,26 @@ pub mod lending_protocol {
         process_borrow(ctx, amount)
     }
 
+    pub fn approve_borrow_allowance(ctx: Context<ApproveBorrowAllowance>, amount: u64) -> Result<()> {
+        process_approve_borrow_allowance(ctx, amount)
+    }
+
+    pub fn borrow_delegated(ctx: Context<BorrowDelegated>, amount: u64) -> Result<()> {
+        process_borrow_delegated(ctx, amount)
+    }
+
+    pub fn borrow_term(ctx: Context<BorrowTerm>, loan_id: u64, amount: u64, duration: i64) -> Result<()> {
+        process_borrow_term(ctx, loan_id, amount, duration)
+    }
+
+    pub fn repay_term(ctx: Context<RepayTerm>) -> Result<()> {
+        process_repay_term(ctx)
+    }
+
+    pub fn liquidate_term_loan(ctx: Context<LiquidateTermLoan>) -> Result<()> {
+        process_liquidate_term_loan(ctx)
+    }
+
     pub fn repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
         process_repay(ctx, amount)
     }
@@ -56,4 +96,30 @@ pub mod lending_protocol {
     pub fn liquidate(ctx: Context<Liquidate>) -> Result<()> {
         process_liquidate(ctx)
     }
+
+    pub fn flag_unhealthy(ctx: Context<FlagUnhealthy>) -> Result<()> {
+        process_flag_unhealthy(ctx)
+    }
+
+    #[cfg(feature = "mock-oracle")]
+    pub fn init_mock_price(
+        ctx: Context<InitMockPrice>,
+        feed_id: [u8; 32],
+        price: i64,
+        expo: i32,
+        conf: u64,
+    ) -> Result<()> {
+        process_init_mock_price(ctx, feed_id, price, expo, conf)
+    }
+
+    #[cfg(feature = "mock-oracle")]
+    pub fn set_mock_price(
+        ctx: Context<SetMockPrice>,
+        price: i64,
+        expo: i32,
+        conf: u64,
+        publish_time: i64,
+    ) -> Result<()> {
+        process_set_mock_price(ctx, price, expo, conf, publish_time)
+    }
 }
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+use crate::constants::MAXIMUM_AGE;
+#[cfg(feature = "mock-oracle")]
+use crate::error::ErrorCode;
+#[cfg(feature = "mock-oracle")]
+use crate::state::MockPrice;
+
+/// 価格フィードアカウントの型
+/// `mock-oracle` featureが有効な場合は、プログラムが所有するMockPriceを使用する
+#[cfg(not(feature = "mock-oracle"))]
+pub type PriceFeed = PriceUpdateV2;
+#[cfg(feature = "mock-oracle")]
+pub type PriceFeed = MockPrice;
+
+/// オラクルから取得した価格
+/// Pythの価格形式に合わせる: actual_price = price * 10^exponent
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+/// 価格オラクルの抽象化
+/// borrow / liquidate の評価ロジックは、このトレイトを通して価格を取得する
+pub trait PriceOracle {
+    /// フィードID（16進数文字列）に対応する価格を取得する
+    /// MAXIMUM_AGE秒より古い価格はエラーになる
+    fn get_price(&self, clock: &Clock, feed_id: &str) -> Result<OraclePrice>;
+}
+
+impl PriceOracle for PriceUpdateV2 {
+    fn get_price(&self, clock: &Clock, feed_id: &str) -> Result<OraclePrice> {
+        let feed_id = get_feed_id_from_hex(feed_id)?;
+        let price = self.get_price_no_older_than(clock, MAXIMUM_AGE, &feed_id)?;
+
+        Ok(OraclePrice {
+            price: price.price,
+            conf: price.conf,
+            exponent: price.exponent,
+            publish_time: price.publish_time,
+        })
+    }
+}
+
+#[cfg(feature = "mock-oracle")]
+impl PriceOracle for MockPrice {
+    fn get_price(&self, clock: &Clock, feed_id: &str) -> Result<OraclePrice> {
+        require!(self.feed_id == get_feed_id_from_hex(feed_id)?, ErrorCode::InvalidPriceFeed);
+
+        // Pythと同様に古い価格を拒否する
+        let oldest_allowed = clock.unix_timestamp.saturating_sub(MAXIMUM_AGE as i64);
+        require!(self.publish_time >= oldest_allowed, ErrorCode::StalePrice);
+
+        Ok(OraclePrice {
+            price: self.price,
+            conf: self.conf,
+            exponent: self.expo,
+            publish_time: self.publish_time,
+        })
+    }
+}
@@ -17,7 +17,7 @@ pub struct Bank {
     pub total_borrowed_shares: u64,
     /// ローンが担保不足と判定され清算可能になるLTV閾値
     pub liquidation_threshold: u64,
-    /// 清算時に付与されるボーナスのパーセンテージ
+    /// 清算時に付与されるボーナスのパーセンテージ（オークション有効時は最大値）
     pub liquidation_bonus: u64,
     /// 清算可能な担保のパーセンテージ
     pub liquidation_close_factor: u64,
@@ -27,6 +27,25 @@ pub struct Bank {
     pub last_updated: i64,
     /// 利率（basis points: 500 = 5%）
     pub interest_rate: u64,
+    /// ダッチオークション方式の清算でボーナスが最大になるまでの秒数（0 = 固定ボーナス）
+    pub liquidation_auction_duration: u64,
+    /// 固定期間ローンの年利（basis points）。借入時点の値がTermLoanに固定される
+    pub term_interest_rate: u64,
+    /// 満期後に適用されるペナルティ年利（basis points）
+    pub term_penalty_rate: u64,
+    /// Bankの現在の固定期間ローンの総元本
+    pub total_term_borrowed: u64,
+}
+
+/// プロトコル全体の設定（PDA: ["config"]、プログラムのupgrade authorityが1回だけ作成する）
+/// クラスタごとに異なるMintアドレスを、コードに埋め込まずに指定する
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    /// 設定を作成したアドレス（プログラムのupgrade authority）
+    pub authority: Pubkey,
+    /// 見積もり通貨（USDC）のMintアドレス。init_userではこのMintのBankのみを受け付ける
+    pub usdc_mint: Pubkey,
 }
 
 // チャレンジ: 複数のアセットに対応するために、"all_deposited_assets"と"all_borrowed_assets"を保存するようにユーザー状態をどのように更新しますか？
@@ -53,8 +72,73 @@ pub struct User {
     pub borrowed_usdc_shares: u64,
     /// USDCのMintアドレス
     pub usdc_address: Pubkey,
+    /// SOL側（USDC以外）の預金Mintアドレス（本人の最初の預金で記録される）
+    pub sol_address: Pubkey,
     /// ユーザーの現在のヘルスファクター
     pub health_factor: u64,
     /// 最終更新タイムスタンプ
     pub last_updated: i64,
+    /// flag_unhealthyで清算可能と記録されたタイムスタンプ（0 = 未記録）
+    pub unhealthy_since: i64,
+    /// SOL Bankからの固定期間ローンの元本合計（ヘルスファクター計算に含まれる）
+    pub term_borrowed_sol: u64,
+    /// USDC Bankからの固定期間ローンの元本合計（ヘルスファクター計算に含まれる）
+    pub term_borrowed_usdc: u64,
+}
+
+/// 固定期間・固定金利のローン
+/// 利率は借入時点で固定され、満期後はペナルティ利率が上乗せされる
+#[account]
+#[derive(InitSpace)]
+pub struct TermLoan {
+    /// 借り手のウォレットの公開鍵
+    pub owner: Pubkey,
+    /// 借入アセットのMintアドレス
+    pub mint_address: Pubkey,
+    /// ローンの識別子（借り手ごとに一意）
+    pub loan_id: u64,
+    /// 借入元本
+    pub principal: u64,
+    /// 借入時に固定された年利（basis points）
+    pub interest_rate: u64,
+    /// 満期後のペナルティ年利（basis points）
+    pub penalty_rate: u64,
+    /// 借入タイムスタンプ
+    pub start_time: i64,
+    /// 満期タイムスタンプ
+    pub maturity: i64,
+}
+
+/// 借入能力の委任（預金者が別のウォレットに、自分の担保に対する借入を許可する）
+#[account]
+#[derive(InitSpace)]
+pub struct BorrowAllowance {
+    /// 借入能力を委任するユーザー（担保と借入はこのユーザーに記録される）
+    pub delegator: Pubkey,
+    /// 委任先のウォレット
+    pub delegate: Pubkey,
+    /// 借入可能なアセットのMintアドレス
+    pub mint_address: Pubkey,
+    /// 残りの借入可能額（借入のたびに減少）
+    pub remaining_amount: u64,
+}
+
+/// ローカルテスト用の価格アカウント（`mock-oracle` feature有効時のみ）
+/// PythのPriceUpdateV2の代わりに、任意の価格を設定して清算シナリオなどをオフラインで検証できる
+#[cfg(feature = "mock-oracle")]
+#[account]
+#[derive(InitSpace)]
+pub struct MockPrice {
+    /// 価格を更新する権限を持つアドレス
+    pub authority: Pubkey,
+    /// PythのフィードID
+    pub feed_id: [u8; 32],
+    /// 価格（actual_price = price * 10^expo）
+    pub price: i64,
+    /// 価格のexponent
+    pub expo: i32,
+    /// 信頼区間
+    pub conf: u64,
+    /// 価格の公開タイムスタンプ
+    pub publish_time: i64,
 }
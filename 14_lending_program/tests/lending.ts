@@ -6,6 +6,7 @@ import {
   PublicKey,
   SystemProgram,
   LAMPORTS_PER_SOL,
+  SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import {
   createMint,
@@ -17,6 +18,9 @@ import {
 } from "@solana/spl-token";
 import { assert } from "chai";
 
+// Pythの代わりにMockPriceアカウントで価格を設定するため、
+// mock-oracle featureでビルドしたプログラムに対して実行する
+//   anchor test -- --features mock-oracle
 describe("lending", () => {
   // プロバイダーの設定
   const provider = anchor.AnchorProvider.env();
@@ -28,6 +32,8 @@ describe("lending", () => {
   // テスト用のキーペア
   const admin = Keypair.generate();
   const user = Keypair.generate();
+  const delegate = Keypair.generate();
+  const stranger = Keypair.generate();
 
   // Mintアドレス
   let solMint: PublicKey;
@@ -39,6 +45,9 @@ describe("lending", () => {
   let usdcBankPda: PublicKey;
   let usdcBankBump: number;
 
+  // Config PDA
+  let configPda: PublicKey;
+
   // Treasury PDA
   let solTreasuryPda: PublicKey;
   let usdcTreasuryPda: PublicKey;
@@ -51,14 +60,199 @@ describe("lending", () => {
   let userSolAta: PublicKey;
   let userUsdcAta: PublicKey;
 
+  // モック価格アカウント（PythのフィードIDごとのPDA）
+  let solPricePda: PublicKey;
+  let usdcPricePda: PublicKey;
+
   // テスト用の定数
   const LIQUIDATION_THRESHOLD = 80; // 80%
   const MAX_LTV = 70; // 70%
   const LIQUIDATION_BONUS = 5; // 5%ボーナス
   const LIQUIDATION_CLOSE_FACTOR = 50; // 50%清算
   const INTEREST_RATE = 500; // 年利5% (basis points)
+  const LIQUIDATION_AUCTION_DURATION = 0; // 0 = 固定ボーナス（オークション無効）
+  const TERM_INTEREST_RATE = 800; // 固定期間ローン年利8% (basis points)
+  const TERM_PENALTY_RATE = 2000; // 満期後のペナルティ年利20% (basis points)
   const DEPOSIT_AMOUNT = 1_000_000_000; // 1 SOL (9 decimals)
   const USDC_DEPOSIT_AMOUNT = 100_000_000; // 100 USDC (6 decimals)
+  const SOL_USD_FEED_ID = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+  const USDC_USD_FEED_ID = "eaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a";
+  const PRICE_EXPO = -8;
+  const SOL_PRICE = 100 * 10 ** 8; // $100
+  const USDC_PRICE = 1 * 10 ** 8; // $1
+  const BORROW_AMOUNT = 10_000_000; // 10 USDC
+
+  // BPF Upgradeable Loader（ProgramDataアカウントの所有者）
+  const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+    "BPFLoaderUpgradeab1e11111111111111111111111"
+  );
+
+  // バリデータの現在時刻（Clock sysvarのunix_timestamp）
+  const getClockTime = async (): Promise<number> => {
+    const clock = await connection.getAccountInfo(SYSVAR_CLOCK_PUBKEY);
+    return Number(clock.data.readBigInt64LE(32));
+  };
+
+  // 固定期間ローンのPDA
+  const findTermLoanPda = (owner: PublicKey, mint: PublicKey, loanId: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("term_loan"),
+        owner.toBuffer(),
+        mint.toBuffer(),
+        new anchor.BN(loanId).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  // SOL側のMintを担保として預金した借り手を新しく用意する
+  const setupBorrower = async (
+    collateralAmount: number,
+    collateralMint: PublicKey = solMint
+  ) => {
+    const borrower = Keypair.generate();
+    const airdrop = await connection.requestAirdrop(
+      borrower.publicKey,
+      2 * LAMPORTS_PER_SOL
+    );
+    await connection.confirmTransaction(airdrop);
+
+    const [borrowerAccountPda] = PublicKey.findProgramAddressSync(
+      [borrower.publicKey.toBuffer()],
+      program.programId
+    );
+    const [collateralBankPda] = PublicKey.findProgramAddressSync(
+      [collateralMint.toBuffer()],
+      program.programId
+    );
+    const [collateralTreasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), collateralMint.toBuffer()],
+      program.programId
+    );
+    const borrowerCollateralAta = await createAssociatedTokenAccount(
+      connection,
+      borrower,
+      collateralMint,
+      borrower.publicKey
+    );
+    await mintTo(
+      connection,
+      admin,
+      collateralMint,
+      borrowerCollateralAta,
+      admin,
+      collateralAmount
+    );
+
+    await program.methods
+      .initUser()
+      .accounts({
+        signer: borrower.publicKey,
+        config: configPda,
+        usdcMint: usdcMint,
+        usdcBank: usdcBankPda,
+        userAccount: borrowerAccountPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([borrower])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(collateralAmount))
+      .accounts({
+        signer: borrower.publicKey,
+        mint: collateralMint,
+        bank: collateralBankPda,
+        bankTokenAccount: collateralTreasuryPda,
+        userAccount: borrowerAccountPda,
+        userTokenAccount: borrowerCollateralAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([borrower])
+      .rpc();
+
+    return { borrower, borrowerAccountPda, borrowerCollateralAta };
+  };
+
+  // 標準のパラメータでMintとBankを新しく用意する（誰でもinit_bankを呼び出せる）
+  const setupBank = async () => {
+    const mint = await createMint(connection, admin, admin.publicKey, null, 9);
+    const [bankPda] = PublicKey.findProgramAddressSync(
+      [mint.toBuffer()],
+      program.programId
+    );
+    const [treasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), mint.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initBank(
+        new anchor.BN(LIQUIDATION_THRESHOLD),
+        new anchor.BN(MAX_LTV),
+        new anchor.BN(LIQUIDATION_BONUS),
+        new anchor.BN(LIQUIDATION_CLOSE_FACTOR),
+        new anchor.BN(INTEREST_RATE),
+        new anchor.BN(LIQUIDATION_AUCTION_DURATION),
+        new anchor.BN(TERM_INTEREST_RATE),
+        new anchor.BN(TERM_PENALTY_RATE)
+      )
+      .accounts({
+        signer: admin.publicKey,
+        mint,
+        bank: bankPda,
+        bankTokenAccount: treasuryPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    return { mint, bankPda, treasuryPda };
+  };
+
+  // 返済用のUSDCを持つ第三者の清算者を新しく用意する
+  const setupLiquidator = async (usdcAmount: number) => {
+    const liquidator = Keypair.generate();
+    const airdrop = await connection.requestAirdrop(
+      liquidator.publicKey,
+      2 * LAMPORTS_PER_SOL
+    );
+    await connection.confirmTransaction(airdrop);
+
+    const liquidatorUsdcAta = await createAssociatedTokenAccount(
+      connection,
+      liquidator,
+      usdcMint,
+      liquidator.publicKey
+    );
+    await mintTo(connection, admin, usdcMint, liquidatorUsdcAta, admin, usdcAmount);
+
+    return { liquidator, liquidatorUsdcAta };
+  };
+
+  // モック価格を更新する（publish_timeを省略した場合はバリデータの現在時刻）
+  const setMockPrice = async (
+    mockPrice: PublicKey,
+    price: number,
+    publishTime?: number
+  ) => {
+    await program.methods
+      .setMockPrice(
+        new anchor.BN(price),
+        PRICE_EXPO,
+        new anchor.BN(0),
+        new anchor.BN(publishTime ?? (await getClockTime()))
+      )
+      .accounts({
+        signer: admin.publicKey,
+        mockPrice,
+      })
+      .signers([admin])
+      .rpc();
+  };
 
   before(async () => {
     // Airdrop SOL to admin and user
@@ -74,6 +268,14 @@ describe("lending", () => {
     );
     await connection.confirmTransaction(userAirdrop);
 
+    for (const wallet of [delegate, stranger]) {
+      const airdrop = await connection.requestAirdrop(
+        wallet.publicKey,
+        2 * LAMPORTS_PER_SOL
+      );
+      await connection.confirmTransaction(airdrop);
+    }
+
     // SOL Mintの作成（テスト用のwrapped SOL的なもの）
     solMint = await createMint(
       connection,
@@ -103,6 +305,12 @@ describe("lending", () => {
       program.programId
     );
 
+    // Config PDAの計算
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+
     // Treasury PDAの計算
     [solTreasuryPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("treasury"), solMint.toBuffer()],
@@ -120,6 +328,17 @@ describe("lending", () => {
       program.programId
     );
 
+    // モック価格PDAの計算
+    [solPricePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mock_price"), Buffer.from(SOL_USD_FEED_ID, "hex")],
+      program.programId
+    );
+
+    [usdcPricePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mock_price"), Buffer.from(USDC_USD_FEED_ID, "hex")],
+      program.programId
+    );
+
     // ユーザーのATAを作成
     userSolAta = await createAssociatedTokenAccount(
       connection,
@@ -170,7 +389,10 @@ describe("lending", () => {
           new anchor.BN(MAX_LTV),
           new anchor.BN(LIQUIDATION_BONUS),
           new anchor.BN(LIQUIDATION_CLOSE_FACTOR),
-          new anchor.BN(INTEREST_RATE)
+          new anchor.BN(INTEREST_RATE),
+          new anchor.BN(LIQUIDATION_AUCTION_DURATION),
+          new anchor.BN(TERM_INTEREST_RATE),
+          new anchor.BN(TERM_PENALTY_RATE)
         )
         .accounts({
           signer: admin.publicKey,
@@ -200,6 +422,12 @@ describe("lending", () => {
       assert.equal(bankAccount.liquidationBonus.toNumber(), LIQUIDATION_BONUS);
       assert.equal(bankAccount.liquidationCloseFactor.toNumber(), LIQUIDATION_CLOSE_FACTOR);
       assert.equal(bankAccount.interestRate.toNumber(), INTEREST_RATE);
+      assert.equal(
+        bankAccount.liquidationAuctionDuration.toNumber(),
+        LIQUIDATION_AUCTION_DURATION
+      );
+      assert.equal(bankAccount.termInterestRate.toNumber(), TERM_INTEREST_RATE);
+      assert.equal(bankAccount.termPenaltyRate.toNumber(), TERM_PENALTY_RATE);
       assert.equal(bankAccount.totalDeposits.toNumber(), 0);
       assert.equal(bankAccount.totalDepositShares.toNumber(), 0);
     });
@@ -211,7 +439,10 @@ describe("lending", () => {
           new anchor.BN(MAX_LTV),
           new anchor.BN(LIQUIDATION_BONUS),
           new anchor.BN(LIQUIDATION_CLOSE_FACTOR),
-          new anchor.BN(INTEREST_RATE)
+          new anchor.BN(INTEREST_RATE),
+          new anchor.BN(LIQUIDATION_AUCTION_DURATION),
+          new anchor.BN(TERM_INTEREST_RATE),
+          new anchor.BN(TERM_PENALTY_RATE)
         )
         .accounts({
           signer: admin.publicKey,
@@ -232,13 +463,93 @@ describe("lending", () => {
     });
   });
 
+  describe("init_config", () => {
+    // プログラムのProgramDataアカウント（upgrade authorityを保持する）
+    const programDataPda = () =>
+      PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+      )[0];
+
+    it("upgrade authority以外は設定を作成できない", async () => {
+      try {
+        await program.methods
+          .initConfig()
+          .accounts({
+            signer: admin.publicKey,
+            usdcMint: usdcMint,
+            usdcBank: usdcBankPda,
+            config: configPda,
+            program: program.programId,
+            programData: programDataPda(),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+
+        assert.fail("upgrade authority以外が設定を作成できてしまった");
+      } catch (error) {
+        assert.include(error.message, "NotUpgradeAuthority");
+        console.log("upgrade authority以外の設定作成は正しく拒否された");
+      }
+    });
+
+    it("upgrade authorityが見積もり通貨を設定できる", async () => {
+      const tx = await program.methods
+        .initConfig()
+        .accounts({
+          signer: provider.wallet.publicKey,
+          usdcMint: usdcMint,
+          usdcBank: usdcBankPda,
+          config: configPda,
+          program: program.programId,
+          programData: programDataPda(),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("設定作成 tx:", tx);
+
+      const config = await program.account.config.fetch(configPda);
+      assert.equal(
+        config.authority.toBase58(),
+        provider.wallet.publicKey.toBase58()
+      );
+      assert.equal(config.usdcMint.toBase58(), usdcMint.toBase58());
+    });
+  });
+
   describe("init_user", () => {
+    it("見積もり通貨以外のMintでは初期化できない", async () => {
+      try {
+        await program.methods
+          .initUser()
+          .accounts({
+            signer: user.publicKey,
+            config: configPda,
+            usdcMint: solMint,
+            usdcBank: solBankPda,
+            userAccount: userAccountPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+
+        assert.fail("SOLのMintでユーザーが初期化されてしまった");
+      } catch (error) {
+        assert.include(error.message, "InvalidQuoteMint");
+        console.log("見積もり通貨以外のMintは正しく拒否された");
+      }
+    });
+
     it("ユーザーアカウントを初期化できる", async () => {
       const tx = await program.methods
         .initUser()
         .accounts({
           signer: user.publicKey,
+          config: configPda,
           usdcMint: usdcMint,
+          usdcBank: usdcBankPda,
           userAccount: userAccountPda,
           systemProgram: SystemProgram.programId,
         })
@@ -398,6 +709,113 @@ describe("lending", () => {
     });
   });
 
+  describe("deposit_for", () => {
+    it("他のユーザーのためにSOLを預金できる", async () => {
+      // adminが自分のトークンで、userのアカウントに預金する
+      const adminSolAta = await createAssociatedTokenAccount(
+        connection,
+        admin,
+        solMint,
+        admin.publicKey
+      );
+      await mintTo(connection, admin, solMint, adminSolAta, admin, DEPOSIT_AMOUNT);
+
+      const before = await program.account.user.fetch(userAccountPda);
+
+      const tx = await program.methods
+        .depositFor(user.publicKey, new anchor.BN(DEPOSIT_AMOUNT))
+        .accounts({
+          signer: admin.publicKey,
+          mint: solMint,
+          bank: solBankPda,
+          bankTokenAccount: solTreasuryPda,
+          beneficiaryAccount: userAccountPda,
+          signerTokenAccount: adminSolAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      console.log("代理預金 tx:", tx);
+
+      const after = await program.account.user.fetch(userAccountPda);
+      assert.equal(
+        after.depositedSol.toNumber(),
+        before.depositedSol.toNumber() + DEPOSIT_AMOUNT
+      );
+    });
+
+    it("SOL側のMintが未記録のユーザーには、任意のBankのMintを記録させられない", async () => {
+      // まだSOL側の預金がないユーザー
+      const beneficiary = Keypair.generate();
+      const airdrop = await connection.requestAirdrop(
+        beneficiary.publicKey,
+        LAMPORTS_PER_SOL
+      );
+      await connection.confirmTransaction(airdrop);
+
+      const [beneficiaryAccountPda] = PublicKey.findProgramAddressSync(
+        [beneficiary.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initUser()
+        .accounts({
+          signer: beneficiary.publicKey,
+          config: configPda,
+          usdcMint: usdcMint,
+          usdcBank: usdcBankPda,
+          userAccount: beneficiaryAccountPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([beneficiary])
+        .rpc();
+
+      // 誰でも作成できるBankを使って、受益者のSOL側のMintを固定しようとする
+      const {
+        mint: junkMint,
+        bankPda: junkBankPda,
+        treasuryPda: junkTreasuryPda,
+      } = await setupBank();
+
+      const adminJunkAta = await createAssociatedTokenAccount(
+        connection,
+        admin,
+        junkMint,
+        admin.publicKey
+      );
+      await mintTo(connection, admin, junkMint, adminJunkAta, admin, DEPOSIT_AMOUNT);
+
+      try {
+        await program.methods
+          .depositFor(beneficiary.publicKey, new anchor.BN(DEPOSIT_AMOUNT))
+          .accounts({
+            signer: admin.publicKey,
+            mint: junkMint,
+            bank: junkBankPda,
+            bankTokenAccount: junkTreasuryPda,
+            beneficiaryAccount: beneficiaryAccountPda,
+            signerTokenAccount: adminJunkAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+
+        assert.fail("代理預金で受益者のSOL側のMintが記録されてしまった");
+      } catch (error) {
+        assert.include(error.message, "UnsupportedMint");
+        console.log("未記録のMintでの代理預金は正しく拒否された");
+      }
+
+      const userAccount = await program.account.user.fetch(beneficiaryAccountPda);
+      assert.equal(userAccount.solAddress.toBase58(), PublicKey.default.toBase58());
+    });
+  });
+
   describe("repay", () => {
     it("借入がない状態での返済は失敗する", async () => {
       try {
@@ -425,6 +843,863 @@ describe("lending", () => {
     });
   });
 
+  describe("close_user", () => {
+    it("預金が残っている場合は閉じられない", async () => {
+      try {
+        await program.methods
+          .closeUser()
+          .accounts({
+            signer: user.publicKey,
+            userAccount: userAccountPda,
+          })
+          .signers([user])
+          .rpc();
+
+        assert.fail("預金が残っている状態でアカウントを閉じてしまった");
+      } catch (error) {
+        assert.include(error.message, "UserHasOpenPositions");
+        console.log("預金が残っている状態でのクローズは正しく拒否された");
+      }
+    });
+  });
+
+  describe("mock oracle", () => {
+    it("モック価格を初期化できる", async () => {
+      await program.methods
+        .initMockPrice(
+          Array.from(Buffer.from(SOL_USD_FEED_ID, "hex")),
+          new anchor.BN(SOL_PRICE),
+          PRICE_EXPO,
+          new anchor.BN(0)
+        )
+        .accounts({
+          signer: admin.publicKey,
+          mockPrice: solPricePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      await program.methods
+        .initMockPrice(
+          Array.from(Buffer.from(USDC_USD_FEED_ID, "hex")),
+          new anchor.BN(USDC_PRICE),
+          PRICE_EXPO,
+          new anchor.BN(0)
+        )
+        .accounts({
+          signer: admin.publicKey,
+          mockPrice: usdcPricePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const solPrice = await program.account.mockPrice.fetch(solPricePda);
+      assert.equal(solPrice.authority.toBase58(), admin.publicKey.toBase58());
+      assert.equal(
+        Buffer.from(solPrice.feedId).toString("hex"),
+        SOL_USD_FEED_ID
+      );
+      assert.equal(solPrice.price.toNumber(), SOL_PRICE);
+      assert.equal(solPrice.expo, PRICE_EXPO);
+    });
+
+    it("権限のないアカウントは価格を更新できない", async () => {
+      try {
+        await program.methods
+          .setMockPrice(
+            new anchor.BN(1),
+            PRICE_EXPO,
+            new anchor.BN(0),
+            new anchor.BN(await getClockTime())
+          )
+          .accounts({
+            signer: user.publicKey,
+            mockPrice: solPricePda,
+          })
+          .signers([user])
+          .rpc();
+
+        assert.fail("権限のないアカウントが価格を更新してしまった");
+      } catch (error) {
+        assert.include(error.message, "ConstraintRaw");
+      }
+
+      const solPrice = await program.account.mockPrice.fetch(solPricePda);
+      assert.equal(solPrice.price.toNumber(), SOL_PRICE);
+    });
+
+    it("古い価格では借入できない", async () => {
+      await setMockPrice(solPricePda, SOL_PRICE, (await getClockTime()) - 1000);
+
+      try {
+        await program.methods
+          .borrow(new anchor.BN(BORROW_AMOUNT))
+          .accounts({
+            signer: user.publicKey,
+            mint: usdcMint,
+            bank: usdcBankPda,
+            bankTokenAccount: usdcTreasuryPda,
+            userAccount: userAccountPda,
+            userTokenAccount: userUsdcAta,
+            priceUpdate: solPricePda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+
+        assert.fail("古い価格で借入できてしまった");
+      } catch (error) {
+        assert.include(error.message, "StalePrice");
+        console.log("古い価格での借入は正しく拒否された");
+      }
+    });
+
+    it("モック価格で評価した担保でUSDCを借入できる", async () => {
+      await setMockPrice(solPricePda, SOL_PRICE);
+
+      const before = await program.account.user.fetch(userAccountPda);
+
+      await program.methods
+        .borrow(new anchor.BN(BORROW_AMOUNT))
+        .accounts({
+          signer: user.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          userAccount: userAccountPda,
+          userTokenAccount: userUsdcAta,
+          priceUpdate: solPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      const after = await program.account.user.fetch(userAccountPda);
+      assert.equal(
+        after.borrowedUsdc.toNumber(),
+        before.borrowedUsdc.toNumber() + BORROW_AMOUNT
+      );
+    });
+  });
+
+  describe("borrow_delegated", () => {
+    const ALLOWANCE_AMOUNT = 5_000_000; // 5 USDC
+    const DELEGATED_BORROW_AMOUNT = 3_000_000; // 3 USDC
+
+    // 委任元userから委任先へのUSDC借入可能額のPDA
+    const findBorrowAllowancePda = (delegateKey: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("borrow_allowance"),
+          user.publicKey.toBuffer(),
+          delegateKey.toBuffer(),
+          usdcMint.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+    const borrowDelegated = (signer: Keypair, amount: number) => {
+      const borrowAllowancePda = findBorrowAllowancePda(signer.publicKey);
+
+      return program.methods
+        .borrowDelegated(new anchor.BN(amount))
+        .accounts({
+          signer: signer.publicKey,
+          delegator: user.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          delegatorAccount: userAccountPda,
+          borrowAllowance: borrowAllowancePda,
+          priceUpdate: solPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    it("委任先に借入可能額を設定できる", async () => {
+      const borrowAllowancePda = findBorrowAllowancePda(delegate.publicKey);
+
+      await program.methods
+        .approveBorrowAllowance(new anchor.BN(ALLOWANCE_AMOUNT))
+        .accounts({
+          signer: user.publicKey,
+          delegate: delegate.publicKey,
+          mint: usdcMint,
+          userAccount: userAccountPda,
+          borrowAllowance: borrowAllowancePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      const allowance = await program.account.borrowAllowance.fetch(
+        borrowAllowancePda
+      );
+      assert.equal(allowance.delegator.toBase58(), user.publicKey.toBase58());
+      assert.equal(allowance.delegate.toBase58(), delegate.publicKey.toBase58());
+      assert.equal(allowance.remainingAmount.toNumber(), ALLOWANCE_AMOUNT);
+    });
+
+    it("委任先は借入可能額の範囲で委任元の担保から借入できる", async () => {
+      await setMockPrice(solPricePda, SOL_PRICE);
+
+      const before = await program.account.user.fetch(userAccountPda);
+
+      await borrowDelegated(delegate, DELEGATED_BORROW_AMOUNT);
+
+      // 借入は委任元に記録され、トークンは委任先に転送される
+      const after = await program.account.user.fetch(userAccountPda);
+      assert.equal(
+        after.borrowedUsdc.toNumber(),
+        before.borrowedUsdc.toNumber() + DELEGATED_BORROW_AMOUNT
+      );
+
+      const delegateUsdcAta = await getAssociatedTokenAddress(
+        usdcMint,
+        delegate.publicKey
+      );
+      const balance = await connection.getTokenAccountBalance(delegateUsdcAta);
+      assert.equal(Number(balance.value.amount), DELEGATED_BORROW_AMOUNT);
+
+      // 残りの借入可能額が減る
+      const allowance = await program.account.borrowAllowance.fetch(
+        findBorrowAllowancePda(delegate.publicKey)
+      );
+      assert.equal(
+        allowance.remainingAmount.toNumber(),
+        ALLOWANCE_AMOUNT - DELEGATED_BORROW_AMOUNT
+      );
+    });
+
+    it("借入可能額を超える委任借入は失敗する", async () => {
+      try {
+        await borrowDelegated(delegate, DELEGATED_BORROW_AMOUNT);
+
+        assert.fail("借入可能額を超えて借入できてしまった");
+      } catch (error) {
+        assert.include(error.message, "OverBorrowAllowance");
+        console.log("借入可能額を超える委任借入は正しく拒否された");
+      }
+    });
+
+    it("委任されていないウォレットは借入できない", async () => {
+      try {
+        await borrowDelegated(stranger, 1);
+
+        assert.fail("委任されていないウォレットが借入できてしまった");
+      } catch (error) {
+        assert.include(error.message, "AccountNotInitialized");
+        console.log("委任されていないウォレットの借入は正しく拒否された");
+      }
+    });
+  });
+
+  describe("liquidate", () => {
+    const VARIABLE_BORROW_AMOUNT = 10_000_000; // 10 USDC
+    const TERM_BORROW_AMOUNT = 30_000_000; // 30 USDC
+    const TERM_DURATION = 86_400; // 1日
+    const UNHEALTHY_SOL_PRICE = 4_000_000; // ヘルスファクターが80になる価格
+
+    let borrower: Keypair;
+    let borrowerAccountPda: PublicKey;
+    let liquidator: Keypair;
+    let liquidatorUsdcAta: PublicKey;
+
+    const liquidate = () =>
+      program.methods
+        .liquidate()
+        .accounts({
+          liquidator: liquidator.publicKey,
+          borrower: borrower.publicKey,
+          collateralPriceUpdate: solPricePda,
+          borrowedPriceUpdate: usdcPricePda,
+          collateralMint: solMint,
+          borrowedMint: usdcMint,
+          collateralBank: solBankPda,
+          collateralBankTokenAccount: solTreasuryPda,
+          borrowedBank: usdcBankPda,
+          borrowedBankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([liquidator])
+        .rpc();
+
+    before(async () => {
+      ({ borrower, borrowerAccountPda } = await setupBorrower(DEPOSIT_AMOUNT));
+      ({ liquidator, liquidatorUsdcAta } = await setupLiquidator(VARIABLE_BORROW_AMOUNT));
+
+      await setMockPrice(solPricePda, SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+
+      // 変動金利の借入と固定期間ローンの両方を持つ
+      await program.methods
+        .borrow(new anchor.BN(VARIABLE_BORROW_AMOUNT))
+        .accounts({
+          signer: borrower.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          priceUpdate: solPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([borrower])
+        .rpc();
+
+      await program.methods
+        .borrowTerm(
+          new anchor.BN(0),
+          new anchor.BN(TERM_BORROW_AMOUNT),
+          new anchor.BN(TERM_DURATION)
+        )
+        .accounts({
+          signer: borrower.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          termLoan: findTermLoanPda(borrower.publicKey, usdcMint, 0),
+          priceUpdate: solPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([borrower])
+        .rpc();
+    });
+
+    it("担保が十分な場合は清算できない", async () => {
+      try {
+        await liquidate();
+
+        assert.fail("担保が十分な状態で清算できてしまった");
+      } catch (error) {
+        assert.include(error.message, "NotUndercollateralized");
+      }
+    });
+
+    it("第三者が清算でき、清算額は変動金利の借入額が上限になる", async () => {
+      await setMockPrice(solPricePda, UNHEALTHY_SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+
+      const bankBefore = await program.account.bank.fetch(usdcBankPda);
+
+      await liquidate();
+
+      // 借入価値（変動10 + 固定30 USDC）の50%は20 USDCだが、変動金利の借入10 USDCまでしか返済されない
+      const userAccount = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(userAccount.borrowedUsdc.toNumber(), 0);
+      assert.equal(userAccount.borrowedUsdcShares.toNumber(), 0);
+      assert.equal(userAccount.termBorrowedUsdc.toNumber(), TERM_BORROW_AMOUNT);
+
+      // 担保は返済額10 USDCに5%のボーナスを加えた価値分だけ差し引かれる
+      const collateralValue = (VARIABLE_BORROW_AMOUNT * (100 + LIQUIDATION_BONUS)) / 100;
+      const seizedCollateral = (collateralValue * 10 ** 8) / UNHEALTHY_SOL_PRICE;
+      assert.equal(
+        userAccount.depositedSol.toNumber(),
+        DEPOSIT_AMOUNT - seizedCollateral
+      );
+
+      const bankAfter = await program.account.bank.fetch(usdcBankPda);
+      assert.equal(
+        bankBefore.totalBorrowed.toNumber() - bankAfter.totalBorrowed.toNumber(),
+        VARIABLE_BORROW_AMOUNT
+      );
+      assert.equal(
+        bankAfter.totalTermBorrowed.toNumber(),
+        bankBefore.totalTermBorrowed.toNumber()
+      );
+
+      // 清算者は返済額を支払い、差し引かれた担保を受け取る
+      const liquidatorUsdc = await connection.getTokenAccountBalance(liquidatorUsdcAta);
+      assert.equal(Number(liquidatorUsdc.value.amount), 0);
+      const liquidatorSol = await connection.getTokenAccountBalance(
+        await getAssociatedTokenAddress(solMint, liquidator.publicKey)
+      );
+      assert.equal(Number(liquidatorSol.value.amount), seizedCollateral);
+    });
+
+    it("変動金利の借入がない場合は清算できない", async () => {
+      try {
+        await liquidate();
+
+        assert.fail("固定期間ローンのみの状態で清算できてしまった");
+      } catch (error) {
+        assert.include(error.message, "NoVariableDebt");
+        console.log("固定期間ローンは清算の対象外であることを確認した");
+      }
+
+      await setMockPrice(solPricePda, SOL_PRICE);
+    });
+  });
+
+  describe("flag_unhealthy", () => {
+    const AUCTION_DURATION = 1_000_000; // ボーナスが最大になるまでの秒数
+    const AUCTION_BORROW_AMOUNT = 20_000_000; // 20 USDC
+    const UNHEALTHY_SOL_PRICE = 2_000_000; // ヘルスファクターが80になる価格
+
+    let auctionMint: PublicKey;
+    let auctionBankPda: PublicKey;
+    let auctionTreasuryPda: PublicKey;
+    let borrower: Keypair;
+    let borrowerAccountPda: PublicKey;
+    let borrowerCollateralAta: PublicKey;
+    let liquidator: Keypair;
+
+    const flagUnhealthy = (collateralMint: PublicKey) => {
+      const [collateralBankPda] = PublicKey.findProgramAddressSync(
+        [collateralMint.toBuffer()],
+        program.programId
+      );
+      const isUsdcCollateral = collateralMint.equals(usdcMint);
+
+      return program.methods
+        .flagUnhealthy()
+        .accounts({
+          signer: admin.publicKey,
+          collateralPriceUpdate: isUsdcCollateral ? usdcPricePda : solPricePda,
+          borrowedPriceUpdate: isUsdcCollateral ? solPricePda : usdcPricePda,
+          collateralMint,
+          collateralBank: collateralBankPda,
+          userAccount: borrowerAccountPda,
+        })
+        .signers([admin])
+        .rpc();
+    };
+
+    const liquidate = () =>
+      program.methods
+        .liquidate()
+        .accounts({
+          liquidator: liquidator.publicKey,
+          borrower: borrower.publicKey,
+          collateralPriceUpdate: solPricePda,
+          borrowedPriceUpdate: usdcPricePda,
+          collateralMint: auctionMint,
+          borrowedMint: usdcMint,
+          collateralBank: auctionBankPda,
+          collateralBankTokenAccount: auctionTreasuryPda,
+          borrowedBank: usdcBankPda,
+          borrowedBankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([liquidator])
+        .rpc();
+
+    before(async () => {
+      // ダッチオークション方式で清算されるSOL側のBankを用意する
+      auctionMint = await createMint(connection, admin, admin.publicKey, null, 9);
+      [auctionBankPda] = PublicKey.findProgramAddressSync(
+        [auctionMint.toBuffer()],
+        program.programId
+      );
+      [auctionTreasuryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury"), auctionMint.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initBank(
+          new anchor.BN(LIQUIDATION_THRESHOLD),
+          new anchor.BN(MAX_LTV),
+          new anchor.BN(LIQUIDATION_BONUS),
+          new anchor.BN(LIQUIDATION_CLOSE_FACTOR),
+          new anchor.BN(INTEREST_RATE),
+          new anchor.BN(AUCTION_DURATION),
+          new anchor.BN(TERM_INTEREST_RATE),
+          new anchor.BN(TERM_PENALTY_RATE)
+        )
+        .accounts({
+          signer: admin.publicKey,
+          mint: auctionMint,
+          bank: auctionBankPda,
+          bankTokenAccount: auctionTreasuryPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      ({ borrower, borrowerAccountPda, borrowerCollateralAta } =
+        await setupBorrower(DEPOSIT_AMOUNT, auctionMint));
+      ({ liquidator } = await setupLiquidator(AUCTION_BORROW_AMOUNT));
+
+      await setMockPrice(solPricePda, SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+
+      await program.methods
+        .borrow(new anchor.BN(AUCTION_BORROW_AMOUNT))
+        .accounts({
+          signer: borrower.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          priceUpdate: solPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([borrower])
+        .rpc();
+    });
+
+    it("担保が十分な場合は記録されない", async () => {
+      await flagUnhealthy(auctionMint);
+
+      const userAccount = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(userAccount.unhealthySince.toNumber(), 0);
+    });
+
+    it("ユーザーの担保ではないBankは指定できない", async () => {
+      try {
+        await flagUnhealthy(usdcMint);
+
+        assert.fail("担保ではないBankで記録できてしまった");
+      } catch (error) {
+        assert.include(error.message, "InvalidCollateral");
+        console.log("担保ではないBankの指定は正しく拒否された");
+      }
+    });
+
+    it("記録される前はオークション方式で清算できない", async () => {
+      await setMockPrice(solPricePda, UNHEALTHY_SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+
+      try {
+        await liquidate();
+
+        assert.fail("記録される前に清算できてしまった");
+      } catch (error) {
+        assert.include(error.message, "NotFlaggedUnhealthy");
+      }
+    });
+
+    it("担保不足になった時刻を記録し、再度呼び出しても上書きしない", async () => {
+      await flagUnhealthy(auctionMint);
+
+      const flagged = await program.account.user.fetch(borrowerAccountPda);
+      assert.isAbove(flagged.unhealthySince.toNumber(), 0);
+
+      await flagUnhealthy(auctionMint);
+
+      const reflagged = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(
+        reflagged.unhealthySince.toNumber(),
+        flagged.unhealthySince.toNumber()
+      );
+    });
+
+    it("オークション開始直後の清算ボーナスは0から始まる", async () => {
+      await liquidate();
+
+      // 借入価値20 USDCの50%（10 USDC）に対して、ボーナスなしの担保だけが差し引かれる
+      const liquidationValue = (AUCTION_BORROW_AMOUNT * LIQUIDATION_CLOSE_FACTOR) / 100;
+      const seizedCollateral = (liquidationValue * 10 ** 8) / UNHEALTHY_SOL_PRICE;
+
+      const userAccount = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(
+        userAccount.borrowedUsdc.toNumber(),
+        AUCTION_BORROW_AMOUNT - liquidationValue
+      );
+      assert.equal(
+        userAccount.depositedSol.toNumber(),
+        DEPOSIT_AMOUNT - seizedCollateral
+      );
+
+      // 清算後もヘルスファクターは100未満のため、記録は残る
+      assert.isAbove(userAccount.unhealthySince.toNumber(), 0);
+    });
+
+    it("預金でヘルスファクターが回復すると記録がリセットされる", async () => {
+      await setMockPrice(solPricePda, SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+      await mintTo(connection, admin, auctionMint, borrowerCollateralAta, admin, 1);
+
+      await program.methods
+        .deposit(new anchor.BN(1))
+        .accounts({
+          signer: borrower.publicKey,
+          mint: auctionMint,
+          bank: auctionBankPda,
+          bankTokenAccount: auctionTreasuryPda,
+          userAccount: borrowerAccountPda,
+          userTokenAccount: borrowerCollateralAta,
+          collateralPriceUpdate: solPricePda,
+          borrowedPriceUpdate: usdcPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([borrower])
+        .rpc();
+
+      const userAccount = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(userAccount.unhealthySince.toNumber(), 0);
+    });
+  });
+
+  describe("term loan", () => {
+    const TERM_LOAN_AMOUNT = 10_000_000; // 10 USDC
+    const LONG_DURATION = 86_400; // 1日
+    const SHORT_DURATION = 1; // 1秒後に満期
+
+    let borrower: Keypair;
+    let borrowerAccountPda: PublicKey;
+    let adminUsdcAta: PublicKey;
+
+    const borrowTerm = (loanId: number, duration: number) =>
+      program.methods
+        .borrowTerm(
+          new anchor.BN(loanId),
+          new anchor.BN(TERM_LOAN_AMOUNT),
+          new anchor.BN(duration)
+        )
+        .accounts({
+          signer: borrower.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          termLoan: findTermLoanPda(borrower.publicKey, usdcMint, loanId),
+          priceUpdate: solPricePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([borrower])
+        .rpc();
+
+    // adminが清算者として返済総額を支払い、SOLの担保を受け取る
+    const liquidateTermLoan = (loanId: number, collateralMint: PublicKey) => {
+      const [collateralBankPda] = PublicKey.findProgramAddressSync(
+        [collateralMint.toBuffer()],
+        program.programId
+      );
+      const [collateralTreasuryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury"), collateralMint.toBuffer()],
+        program.programId
+      );
+
+      return program.methods
+        .liquidateTermLoan()
+        .accounts({
+          liquidator: admin.publicKey,
+          owner: borrower.publicKey,
+          collateralPriceUpdate: solPricePda,
+          borrowedPriceUpdate: usdcPricePda,
+          collateralMint,
+          borrowedMint: usdcMint,
+          collateralBank: collateralBankPda,
+          collateralBankTokenAccount: collateralTreasuryPda,
+          borrowedBank: usdcBankPda,
+          borrowedBankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          termLoan: findTermLoanPda(borrower.publicKey, usdcMint, loanId),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    };
+
+    before(async () => {
+      ({ borrower, borrowerAccountPda } = await setupBorrower(DEPOSIT_AMOUNT));
+
+      adminUsdcAta = await createAssociatedTokenAccount(
+        connection,
+        admin,
+        usdcMint,
+        admin.publicKey
+      );
+      await mintTo(connection, admin, usdcMint, adminUsdcAta, admin, USDC_DEPOSIT_AMOUNT);
+
+      await setMockPrice(solPricePda, SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+    });
+
+    it("固定期間ローンを借入できる", async () => {
+      const bankBefore = await program.account.bank.fetch(usdcBankPda);
+
+      await borrowTerm(0, LONG_DURATION);
+
+      const termLoan = await program.account.termLoan.fetch(
+        findTermLoanPda(borrower.publicKey, usdcMint, 0)
+      );
+      assert.equal(termLoan.owner.toBase58(), borrower.publicKey.toBase58());
+      assert.equal(termLoan.principal.toNumber(), TERM_LOAN_AMOUNT);
+      assert.equal(termLoan.interestRate.toNumber(), TERM_INTEREST_RATE);
+      assert.equal(termLoan.penaltyRate.toNumber(), TERM_PENALTY_RATE);
+      assert.equal(
+        termLoan.maturity.toNumber() - termLoan.startTime.toNumber(),
+        LONG_DURATION
+      );
+
+      const userAccount = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(userAccount.termBorrowedUsdc.toNumber(), TERM_LOAN_AMOUNT);
+
+      const bankAfter = await program.account.bank.fetch(usdcBankPda);
+      assert.equal(
+        bankAfter.totalTermBorrowed.toNumber(),
+        bankBefore.totalTermBorrowed.toNumber() + TERM_LOAN_AMOUNT
+      );
+    });
+
+    it("固定期間ローンを返済するとTermLoanが閉じられる", async () => {
+      const termLoanPda = findTermLoanPda(borrower.publicKey, usdcMint, 0);
+      const borrowerUsdcAta = await getAssociatedTokenAddress(
+        usdcMint,
+        borrower.publicKey
+      );
+
+      await program.methods
+        .repayTerm()
+        .accounts({
+          signer: borrower.publicKey,
+          mint: usdcMint,
+          bank: usdcBankPda,
+          bankTokenAccount: usdcTreasuryPda,
+          userAccount: borrowerAccountPda,
+          termLoan: termLoanPda,
+          userTokenAccount: borrowerUsdcAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([borrower])
+        .rpc();
+
+      // 借入直後の返済なので利息は発生せず、元本のみが返済される
+      const balance = await connection.getTokenAccountBalance(borrowerUsdcAta);
+      assert.equal(Number(balance.value.amount), 0);
+
+      const userAccount = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(userAccount.termBorrowedUsdc.toNumber(), 0);
+      assert.isNull(await program.account.termLoan.fetchNullable(termLoanPda));
+    });
+
+    it("満期前の固定期間ローンは清算できない", async () => {
+      await borrowTerm(1, LONG_DURATION);
+
+      try {
+        await liquidateTermLoan(1, solMint);
+
+        assert.fail("満期前に清算できてしまった");
+      } catch (error) {
+        assert.include(error.message, "TermLoanNotMatured");
+      }
+    });
+
+    it("担保と借入に同じMintは指定できない", async () => {
+      try {
+        await liquidateTermLoan(1, usdcMint);
+
+        assert.fail("担保と借入に同じMintを指定して清算できてしまった");
+      } catch (error) {
+        assert.include(error.message, "InvalidCollateral");
+        console.log("担保と借入に同じMintの指定は正しく拒否された");
+      }
+    });
+
+    it("ユーザーの担保ではないBankからは清算できない", async () => {
+      const { mint: otherMint } = await setupBank();
+
+      try {
+        await liquidateTermLoan(1, otherMint);
+
+        assert.fail("担保ではないBankから清算できてしまった");
+      } catch (error) {
+        assert.include(error.message, "InvalidCollateral");
+        console.log("担保ではないBankの指定は正しく拒否された");
+      }
+    });
+
+    it("満期を過ぎた固定期間ローンは清算できる", async () => {
+      await borrowTerm(2, SHORT_DURATION);
+
+      // 満期を過ぎるまで待つ
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await setMockPrice(solPricePda, SOL_PRICE);
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+
+      const before = await program.account.user.fetch(borrowerAccountPda);
+      const adminBalanceBefore = await connection.getTokenAccountBalance(adminUsdcAta);
+
+      await liquidateTermLoan(2, solMint);
+
+      // 満期直後なので利息は発生せず、清算者は元本を支払う
+      const adminBalanceAfter = await connection.getTokenAccountBalance(adminUsdcAta);
+      assert.equal(
+        Number(adminBalanceBefore.value.amount) - Number(adminBalanceAfter.value.amount),
+        TERM_LOAN_AMOUNT
+      );
+
+      // 清算者は返済額に5%のボーナスを加えた価値のSOLを受け取る
+      const collateralValue = (TERM_LOAN_AMOUNT * (100 + LIQUIDATION_BONUS)) / 100;
+      const seizedCollateral = (collateralValue * 10 ** 8) / SOL_PRICE;
+
+      const after = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(
+        after.depositedSol.toNumber(),
+        before.depositedSol.toNumber() - seizedCollateral
+      );
+      assert.equal(
+        after.termBorrowedUsdc.toNumber(),
+        before.termBorrowedUsdc.toNumber() - TERM_LOAN_AMOUNT
+      );
+      assert.isNull(
+        await program.account.termLoan.fetchNullable(
+          findTermLoanPda(borrower.publicKey, usdcMint, 2)
+        )
+      );
+    });
+
+    it("担保が返済総額に足りない場合は預金額の全額までが差し引かれる", async () => {
+      await borrowTerm(3, SHORT_DURATION);
+
+      // 満期を過ぎるまで待ち、担保の価値を返済総額より小さくする
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await setMockPrice(solPricePda, SOL_PRICE / 10_000); // $0.01
+      await setMockPrice(usdcPricePda, USDC_PRICE);
+
+      const before = await program.account.user.fetch(borrowerAccountPda);
+      const adminSolAta = await getAssociatedTokenAddress(solMint, admin.publicKey);
+      const adminSolBefore = await connection.getTokenAccountBalance(adminSolAta);
+
+      await liquidateTermLoan(3, solMint);
+
+      const after = await program.account.user.fetch(borrowerAccountPda);
+      assert.equal(after.depositedSol.toNumber(), 0);
+
+      const adminSolAfter = await connection.getTokenAccountBalance(adminSolAta);
+      assert.equal(
+        Number(adminSolAfter.value.amount) - Number(adminSolBefore.value.amount),
+        before.depositedSol.toNumber()
+      );
+
+      await setMockPrice(solPricePda, SOL_PRICE);
+    });
+  });
+
   describe("アカウント状態の確認", () => {
     it("Bankアカウントの状態を確認できる", async () => {
       const solBank = await program.account.bank.fetch(solBankPda);